@@ -9,6 +9,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     let Dictionary {
         valid: valid_guesses,
         answers: possible_answers,
+        ..
     } = Dictionary::<5>::from_file("assets/en-infinite.json").unwrap();
 
     let pattern_cache = Pattern::prepare_all(&valid_guesses, &possible_answers);
@@ -22,18 +23,18 @@ fn criterion_benchmark(c: &mut Criterion) {
     let possible_answers_2 = pattern_2.filter_words(&possible_answers_1);
 
     c.bench_function("bfs:slain:lares", |b| {
-        let bfs_guesser = BfsGuesser;
+        let bfs_guesser = BfsGuesser::default();
 
         b.iter(|| {
-            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_1, &pattern_cache);
+            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_1, &pattern_cache, 0);
         })
     });
 
     c.bench_function("bfs:slain:lares-stalk", |b| {
-        let bfs_guesser = BfsGuesser;
+        let bfs_guesser = BfsGuesser::default();
 
         b.iter(|| {
-            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_2, &pattern_cache);
+            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_2, &pattern_cache, 0);
         })
     });
 
@@ -41,7 +42,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         let bfs_guesser = NaiveGuesser;
 
         b.iter(|| {
-            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_1, &pattern_cache);
+            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_1, &pattern_cache, 0);
         })
     });
 
@@ -49,7 +50,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         let bfs_guesser = NaiveGuesser;
 
         b.iter(|| {
-            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_2, &pattern_cache);
+            let _ = bfs_guesser.rank_guesses(&valid_guesses, &possible_answers_2, &pattern_cache, 0);
         })
     });
 }
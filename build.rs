@@ -0,0 +1,17 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // into OUT_DIR since build scripts mustn't modify the sources, a test checks that
+    // `include/rustybovich.h` matches
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        cbindgen::generate(&crate_dir)
+            .expect("couldn't generate the C header")
+            .write_to_file(format!("{out_dir}/rustybovich.h"));
+    }
+}
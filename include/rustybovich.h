@@ -0,0 +1,164 @@
+#ifndef RUSTYBOVICH_H
+#define RUSTYBOVICH_H
+
+/* Generated from src/ffi.rs by cbindgen, don't edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Size of the buffers for words: up to 4 bytes of UTF-8 for each of the 5 letters and a NUL.
+ */
+#define RB_WORD_BUFFER_LEN 21
+
+typedef enum RbStatus {
+  RB_STATUS_OK = 0,
+  /**
+   * A pointer is null or a string isn't UTF-8.
+   */
+  RB_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * The dictionary couldn't be read or parsed.
+   */
+  RB_STATUS_INVALID_DICTIONARY = 2,
+  /**
+   * The colors don't make a pattern for the guess.
+   */
+  RB_STATUS_INVALID_PATTERN = 3,
+  /**
+   * The guess isn't in the dictionary.
+   */
+  RB_STATUS_UNKNOWN_WORD = 4,
+  RB_STATUS_UNKNOWN_GUESSER = 5,
+  /**
+   * Nothing can be ranked, as no answer is left.
+   */
+  RB_STATUS_NO_CANDIDATES = 6,
+  /**
+   * The index is past the last candidate.
+   */
+  RB_STATUS_OUT_OF_RANGE = 7,
+  /**
+   * A bug in the solver, the solver may not be used anymore.
+   */
+  RB_STATUS_INTERNAL = 8,
+} RbStatus;
+
+/**
+ * A game being solved: the dictionary and the patterns applied so far.
+ */
+typedef struct RbSolver RbSolver;
+
+/**
+ * A guess with its rank, lower is better.
+ */
+typedef struct RbRankedGuess {
+  /**
+   * NUL-terminated UTF-8.
+   */
+  char word[RB_WORD_BUFFER_LEN];
+  float rank;
+} RbRankedGuess;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The message of the last error on this thread, valid until the next call on the thread.
+ */
+const char *rb_last_error(void);
+
+/**
+ * Creates a solver for the dictionary file at `path` into `*solver`, `lies` is the number of
+ * letters colored wrong in every pattern, 0 for regular games.
+ *
+ * # Safety
+ *
+ * `path` must be a NUL-terminated string and `solver` must be valid for writes.
+ */
+enum RbStatus rb_solver_from_file(const char *path, size_t lies, struct RbSolver **solver);
+
+/**
+ * Creates a solver for the dictionary JSON in the `len` bytes at `json` into `*solver`, see
+ * [`rb_solver_from_file`].
+ *
+ * # Safety
+ *
+ * `json` must be valid for reads of `len` bytes and `solver` must be valid for writes.
+ */
+enum RbStatus rb_solver_from_buffer(const char *json,
+                                    size_t len,
+                                    size_t lies,
+                                    struct RbSolver **solver);
+
+/**
+ * Destroys a solver, null is ignored.
+ *
+ * # Safety
+ *
+ * `solver` must come from `rb_solver_from_file` or `rb_solver_from_buffer` and not be used after.
+ */
+void rb_solver_free(struct RbSolver *solver);
+
+/**
+ * Applies the colors `guess` got, e.g. `"crate"` and `"..y.g"`.
+ *
+ * # Safety
+ *
+ * `solver` must be a live solver, `guess` and `colors` NUL-terminated strings.
+ */
+enum RbStatus rb_solver_apply(struct RbSolver *solver, const char *guess, const char *colors);
+
+/**
+ * Starts over with every answer of the dictionary.
+ *
+ * # Safety
+ *
+ * `solver` must be a live solver.
+ */
+enum RbStatus rb_solver_reset(struct RbSolver *solver);
+
+/**
+ * Writes the number of answers left to `*count`.
+ *
+ * # Safety
+ *
+ * `solver` must be a live solver and `count` valid for writes.
+ */
+enum RbStatus rb_solver_candidate_count(const struct RbSolver *solver, size_t *count);
+
+/**
+ * Writes the answer left at `index`, in the order of the dictionary, to `word`.
+ *
+ * # Safety
+ *
+ * `solver` must be a live solver and `word` valid for writes of `RB_WORD_BUFFER_LEN` chars.
+ */
+enum RbStatus rb_solver_candidate(const struct RbSolver *solver,
+                                  size_t index,
+                                  char (*word)[RB_WORD_BUFFER_LEN]);
+
+/**
+ * Ranks the guesses with the guesser named `guesser`, e.g. `"naive"`, and writes the best
+ * `k` of them to `guesses` and how many were written to `*written`.
+ *
+ * # Safety
+ *
+ * `solver` must be a live solver, `guesser` a NUL-terminated string, `guesses` valid for writes
+ * of `k` guesses and `written` valid for writes.
+ */
+enum RbStatus rb_solver_rank(const struct RbSolver *solver,
+                             const char *guesser,
+                             struct RbRankedGuess *guesses,
+                             size_t k,
+                             size_t *written);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTYBOVICH_H */
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::{
+    parallel::*,
+    play::Host,
+    words::{LetterType, Pattern, Word},
+};
+
+/// Adversarial host: instead of settling on an answer it keeps the largest group of answers
+/// consistent with every guess, so the answer stays undecided as long as possible.
+pub struct AbsurdleHost<const N: usize> {
+    possible_answers: Vec<Word<N>>,
+}
+
+impl<const N: usize> AbsurdleHost<N> {
+    pub fn new(answers: Vec<Word<N>>) -> Self {
+        Self {
+            possible_answers: answers,
+        }
+    }
+
+    pub fn possible_answers(&self) -> &[Word<N>] {
+        &self.possible_answers
+    }
+}
+
+impl<const N: usize> Host<N> for AbsurdleHost<N> {
+    fn respond(&mut self, guess: &Word<N>) -> Pattern<N> {
+        let (pattern, bucket) = largest_bucket(guess, &self.possible_answers);
+        self.possible_answers = bucket;
+        pattern
+    }
+
+    fn reveal(&self) -> Vec<Word<N>> {
+        self.possible_answers.clone()
+    }
+}
+
+/// The pattern the adversary answers `guess` with and the answers left after it: the largest
+/// bucket, ties going to the pattern with fewer greens, then fewer yellows.
+pub fn largest_bucket<const N: usize>(
+    guess: &Word<N>,
+    possible_answers: &[Word<N>],
+) -> (Pattern<N>, Vec<Word<N>>) {
+    let count = |pattern: &Pattern<N>, letter_type| {
+        pattern
+            .iter()
+            .filter(|pattern_letter| pattern_letter.letter_type() == letter_type)
+            .count()
+    };
+
+    possible_answers
+        .iter()
+        .cloned()
+        .into_group_map_by(|answer| Pattern::from_guess(guess, answer))
+        .into_iter()
+        .min_by_key(|(pattern, bucket)| {
+            (
+                std::cmp::Reverse(bucket.len()),
+                count(pattern, LetterType::Green),
+                count(pattern, LetterType::Yellow),
+                pattern.to_description(),
+            )
+        })
+        .unwrap_or_else(|| (Pattern::from_guess(guess, guess), vec![]))
+}
+
+/// Searches for the shortest sequence of guesses that wins against [`AbsurdleHost`], the last
+/// guess being the answer. This is a beam search: only `beam_width` most promising positions
+/// are kept after every guess, so with a narrow beam a shorter win may be missed.
+pub fn solve<const N: usize>(
+    valid_guesses: &[Word<N>],
+    answers: &[Word<N>],
+    max_guesses: usize,
+    beam_width: usize,
+) -> Option<Vec<Word<N>>> {
+    let mut positions = vec![(vec![], answers.to_vec())];
+
+    for _ in 0..max_guesses {
+        let won = positions
+            .iter()
+            .find(|(_, possible_answers)| possible_answers.len() == 1);
+        if let Some((guesses, possible_answers)) = won {
+            let mut guesses = guesses.clone();
+            guesses.push(possible_answers[0]);
+            return Some(guesses);
+        }
+
+        let mut next: Vec<_> = positions
+            .par_iter()
+            .enumerate()
+            .flat_map(|(i, (_, possible_answers))| {
+                valid_guesses
+                    .par_iter()
+                    .map(move |guess| (largest_bucket(guess, possible_answers).1.len(), i, guess))
+            })
+            .collect();
+        next.par_sort_unstable_by_key(|&(answers_left, i, _)| (answers_left, i));
+
+        let mut seen = HashSet::new();
+        positions = next
+            .into_iter()
+            .filter_map(|(_, i, guess)| {
+                let (guesses, possible_answers) = &positions[i];
+                let possible_answers = largest_bucket(guess, possible_answers).1;
+                seen.insert(possible_answers.clone()).then(|| {
+                    let mut guesses = guesses.clone();
+                    guesses.push(*guess);
+                    (guesses, possible_answers)
+                })
+            })
+            .take(beam_width)
+            .collect();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_host_keeps_largest_bucket() {
+        let answers = words(&["crate", "trace", "below", "elbow", "bowel"]);
+        let mut host = AbsurdleHost::new(answers);
+
+        let pattern = host.respond(&"light".parse().unwrap());
+        assert_eq!(pattern.to_description(), "y....");
+        assert_eq!(host.possible_answers(), words(&["below", "elbow", "bowel"]));
+
+        let pattern = host.respond(&"below".parse().unwrap());
+        assert!(!pattern.is_solved());
+        assert_eq!(host.possible_answers().len(), 1);
+    }
+
+    #[test]
+    fn test_solve() {
+        let valid = words(&[
+            "crate", "trace", "below", "elbow", "bowel", "light", "crane",
+        ]);
+        let answers = words(&["crate", "trace", "below", "elbow", "bowel"]);
+
+        let guesses = solve(&valid, &answers, 6, 10).unwrap();
+
+        let mut host = AbsurdleHost::new(answers.clone());
+        let patterns: Vec<_> = guesses.iter().map(|guess| host.respond(guess)).collect();
+        assert!(patterns.last().unwrap().is_solved());
+        assert!(!patterns[..patterns.len() - 1]
+            .iter()
+            .any(Pattern::is_solved));
+
+        assert_eq!(solve(&valid, &answers, 1, 10), None);
+    }
+}
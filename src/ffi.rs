@@ -0,0 +1,423 @@
+//! C API for embedding the solver, declared in `include/rustybovich.h`. After changing the API,
+//! regenerate the header with `cbindgen --output include/rustybovich.h`.
+//!
+//! Every function returns an [`RbStatus`], on errors [`rb_last_error`] has the message.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_float, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+    ptr,
+    sync::Arc,
+};
+
+use crate::{
+    guesser::{GuesserRegistry, RankBudget},
+    session::{SessionError, SolverSession},
+    words::{Pattern, Word},
+    Dictionary, WORD_LENGTH as N,
+};
+
+/// Size of the buffers for words: up to 4 bytes of UTF-8 for each of the 5 letters and a NUL.
+pub const RB_WORD_BUFFER_LEN: usize = 21;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RbStatus {
+    Ok = 0,
+    /// A pointer is null or a string isn't UTF-8.
+    InvalidArgument = 1,
+    /// The dictionary couldn't be read or parsed.
+    InvalidDictionary = 2,
+    /// The colors don't make a pattern for the guess.
+    InvalidPattern = 3,
+    /// The guess isn't in the dictionary.
+    UnknownWord = 4,
+    UnknownGuesser = 5,
+    /// Nothing can be ranked, as no answer is left.
+    NoCandidates = 6,
+    /// The index is past the last candidate.
+    OutOfRange = 7,
+    /// A bug in the solver, the solver may not be used anymore.
+    Internal = 8,
+}
+
+/// A guess with its rank, lower is better.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RbRankedGuess {
+    /// NUL-terminated UTF-8.
+    pub word: [c_char; RB_WORD_BUFFER_LEN],
+    pub rank: c_float,
+}
+
+/// A game being solved: the dictionary and the patterns applied so far.
+pub struct RbSolver {
+    guessers: GuesserRegistry<N>,
+    session: SolverSession<'static, N>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Keeps `message` for [`rb_last_error`] and returns `status`.
+fn fail(status: RbStatus, message: impl ToString) -> RbStatus {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+fn session_failure(e: SessionError) -> RbStatus {
+    let status = match e {
+        SessionError::UnknownWord(_) => RbStatus::UnknownWord,
+        SessionError::NoCandidates => RbStatus::NoCandidates,
+    };
+    fail(status, e)
+}
+
+/// Runs `f`, failing with [`RbStatus::Internal`] if it panics, as unwinding into C is undefined.
+fn guarded(f: impl FnOnce() -> RbStatus) -> RbStatus {
+    catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| fail(RbStatus::Internal, "the solver panicked"))
+}
+
+/// The string at `s`, `None` if it's null or not UTF-8.
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn write_word(word: &Word<N>, buffer: &mut [c_char; RB_WORD_BUFFER_LEN]) {
+    let word = word.to_string();
+    buffer.fill(0);
+    for (c, byte) in buffer.iter_mut().zip(word.bytes()) {
+        *c = byte as c_char;
+    }
+}
+
+unsafe fn new_solver(
+    dictionary: Result<Dictionary<N>, Box<dyn std::error::Error>>,
+    lies: usize,
+    solver: *mut *mut RbSolver,
+) -> RbStatus {
+    if solver.is_null() {
+        return fail(RbStatus::InvalidArgument, "the solver pointer is null");
+    }
+    let dictionary = match dictionary {
+        Ok(dictionary) => dictionary,
+        Err(e) => return fail(RbStatus::InvalidDictionary, e),
+    };
+
+    *solver = Box::into_raw(Box::new(RbSolver {
+        guessers: GuesserRegistry::default(),
+        session: SolverSession::shared(Arc::new(dictionary), lies),
+    }));
+    RbStatus::Ok
+}
+
+/// The message of the last error on this thread, valid until the next call on the thread.
+#[no_mangle]
+pub extern "C" fn rb_last_error() -> *const c_char {
+    catch_unwind(|| LAST_ERROR.with(|last| last.borrow().as_ptr())).unwrap_or(c"".as_ptr())
+}
+
+/// Creates a solver for the dictionary file at `path` into `*solver`, `lies` is the number of
+/// letters colored wrong in every pattern, 0 for regular games.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `solver` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_from_file(
+    path: *const c_char,
+    lies: usize,
+    solver: *mut *mut RbSolver,
+) -> RbStatus {
+    guarded(|| {
+        let Some(path) = to_str(path) else {
+            return fail(RbStatus::InvalidArgument, "the path is null or not UTF-8");
+        };
+        new_solver(Dictionary::from_file(Path::new(path)), lies, solver)
+    })
+}
+
+/// Creates a solver for the dictionary JSON in the `len` bytes at `json` into `*solver`, see
+/// [`rb_solver_from_file`].
+///
+/// # Safety
+///
+/// `json` must be valid for reads of `len` bytes and `solver` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_from_buffer(
+    json: *const c_char,
+    len: usize,
+    lies: usize,
+    solver: *mut *mut RbSolver,
+) -> RbStatus {
+    guarded(|| {
+        if json.is_null() {
+            return fail(RbStatus::InvalidArgument, "the buffer is null");
+        }
+        let json = std::slice::from_raw_parts(json.cast::<u8>(), len);
+        let Ok(json) = std::str::from_utf8(json) else {
+            return fail(RbStatus::InvalidArgument, "the buffer isn't UTF-8");
+        };
+        new_solver(Dictionary::from_json(json), lies, solver)
+    })
+}
+
+/// Destroys a solver, null is ignored.
+///
+/// # Safety
+///
+/// `solver` must come from `rb_solver_from_file` or `rb_solver_from_buffer` and not be used after.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_free(solver: *mut RbSolver) {
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        if !solver.is_null() {
+            drop(Box::from_raw(solver));
+        }
+    }));
+}
+
+/// Applies the colors `guess` got, e.g. `"crate"` and `"..y.g"`.
+///
+/// # Safety
+///
+/// `solver` must be a live solver, `guess` and `colors` NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_apply(
+    solver: *mut RbSolver,
+    guess: *const c_char,
+    colors: *const c_char,
+) -> RbStatus {
+    guarded(|| {
+        let (Some(solver), Some(guess), Some(colors)) =
+            (solver.as_mut(), to_str(guess), to_str(colors))
+        else {
+            return fail(
+                RbStatus::InvalidArgument,
+                "an argument is null or not UTF-8",
+            );
+        };
+
+        let Ok(pattern) = Pattern::<N>::from_description(guess, colors) else {
+            let message = format!("`{guess} {colors}` isn't a pattern of {N} letters");
+            return fail(RbStatus::InvalidPattern, message);
+        };
+        match solver.session.apply(pattern) {
+            Ok(()) => RbStatus::Ok,
+            Err(e) => session_failure(e),
+        }
+    })
+}
+
+/// Starts over with every answer of the dictionary.
+///
+/// # Safety
+///
+/// `solver` must be a live solver.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_reset(solver: *mut RbSolver) -> RbStatus {
+    guarded(|| {
+        let Some(solver) = solver.as_mut() else {
+            return fail(RbStatus::InvalidArgument, "the solver is null");
+        };
+        solver.session.reset();
+        RbStatus::Ok
+    })
+}
+
+/// Writes the number of answers left to `*count`.
+///
+/// # Safety
+///
+/// `solver` must be a live solver and `count` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_candidate_count(
+    solver: *const RbSolver,
+    count: *mut usize,
+) -> RbStatus {
+    guarded(|| {
+        let (Some(solver), false) = (solver.as_ref(), count.is_null()) else {
+            return fail(RbStatus::InvalidArgument, "an argument is null");
+        };
+        *count = solver.session.candidates().len();
+        RbStatus::Ok
+    })
+}
+
+/// Writes the answer left at `index`, in the order of the dictionary, to `word`.
+///
+/// # Safety
+///
+/// `solver` must be a live solver and `word` valid for writes of `RB_WORD_BUFFER_LEN` chars.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_candidate(
+    solver: *const RbSolver,
+    index: usize,
+    word: *mut [c_char; RB_WORD_BUFFER_LEN],
+) -> RbStatus {
+    guarded(|| {
+        let (Some(solver), Some(word)) = (solver.as_ref(), word.as_mut()) else {
+            return fail(RbStatus::InvalidArgument, "an argument is null");
+        };
+        let candidates = solver.session.candidates();
+        let Some(candidate) = candidates.get(index) else {
+            let message = format!("{index} is past the {} candidates", candidates.len());
+            return fail(RbStatus::OutOfRange, message);
+        };
+        write_word(candidate, word);
+        RbStatus::Ok
+    })
+}
+
+/// Ranks the guesses with the guesser named `guesser`, e.g. `"naive"`, and writes the best
+/// `k` of them to `guesses` and how many were written to `*written`.
+///
+/// # Safety
+///
+/// `solver` must be a live solver, `guesser` a NUL-terminated string, `guesses` valid for writes
+/// of `k` guesses and `written` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_solver_rank(
+    solver: *const RbSolver,
+    guesser: *const c_char,
+    guesses: *mut RbRankedGuess,
+    k: usize,
+    written: *mut usize,
+) -> RbStatus {
+    guarded(|| {
+        let (Some(solver), Some(name)) = (solver.as_ref(), to_str(guesser)) else {
+            return fail(
+                RbStatus::InvalidArgument,
+                "an argument is null or not UTF-8",
+            );
+        };
+        if (guesses.is_null() && k > 0) || written.is_null() {
+            return fail(RbStatus::InvalidArgument, "an argument is null");
+        }
+        let guesser = match solver.guessers.get(name) {
+            Ok(entry) => entry.guesser(),
+            Err(e) => return fail(RbStatus::UnknownGuesser, e),
+        };
+
+        let ranked = match solver
+            .session
+            .rank(guesser, &RankBudget::unlimited(), &mut |_| {})
+        {
+            Ok(ranking) => ranking.guesses,
+            Err(e) => return session_failure(e),
+        };
+
+        *written = ranked.len().min(k);
+        for (i, (word, rank)) in ranked.iter().take(k).enumerate() {
+            let mut guess = RbRankedGuess {
+                word: [0; RB_WORD_BUFFER_LEN],
+                rank: *rank,
+            };
+            write_word(word, &mut guess.word);
+            ptr::write(guesses.add(i), guess);
+        }
+        RbStatus::Ok
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICTIONARY: &str = r#"{
+        "valid": ["slate", "light", "elbow"],
+        "answers": ["crate", "trace", "below"]
+    }"#;
+
+    fn word(buffer: &[c_char]) -> &str {
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn test_solver() {
+        unsafe {
+            let mut solver = ptr::null_mut();
+            let status =
+                rb_solver_from_buffer(DICTIONARY.as_ptr().cast(), DICTIONARY.len(), 0, &mut solver);
+            assert_eq!(status, RbStatus::Ok);
+
+            let status = rb_solver_apply(solver, c"slate".as_ptr(), c"..gyg".as_ptr());
+            assert_eq!(status, RbStatus::Ok);
+            let mut count = 0;
+            rb_solver_candidate_count(solver, &mut count);
+            assert_eq!(count, 1);
+
+            let mut candidate = [0; RB_WORD_BUFFER_LEN];
+            assert_eq!(rb_solver_candidate(solver, 0, &mut candidate), RbStatus::Ok);
+            assert_eq!(word(&candidate), "trace");
+            assert_eq!(
+                rb_solver_candidate(solver, 1, &mut candidate),
+                RbStatus::OutOfRange
+            );
+
+            let mut guesses = [RbRankedGuess {
+                word: [0; RB_WORD_BUFFER_LEN],
+                rank: 0.0,
+            }; 3];
+            let mut written = 0;
+            let status = rb_solver_rank(
+                solver,
+                c"naive".as_ptr(),
+                guesses.as_mut_ptr(),
+                3,
+                &mut written,
+            );
+            assert_eq!(status, RbStatus::Ok);
+            assert_eq!(written, 3);
+            assert!(guesses[0].rank <= guesses[1].rank);
+
+            let status = rb_solver_apply(solver, c"xxxxx".as_ptr(), c".....".as_ptr());
+            assert_eq!(status, RbStatus::UnknownWord);
+            let message = CStr::from_ptr(rb_last_error()).to_str().unwrap();
+            assert_eq!(message, "xxxxx isn't in the dictionary");
+
+            let status = rb_solver_rank(
+                solver,
+                c"nope".as_ptr(),
+                guesses.as_mut_ptr(),
+                3,
+                &mut written,
+            );
+            assert_eq!(status, RbStatus::UnknownGuesser);
+
+            assert_eq!(rb_solver_reset(solver), RbStatus::Ok);
+            rb_solver_candidate_count(solver, &mut count);
+            assert_eq!(count, 3);
+
+            rb_solver_free(solver);
+        }
+    }
+
+    #[test]
+    fn test_invalid_dictionary() {
+        unsafe {
+            let mut solver = ptr::null_mut();
+            let status = rb_solver_from_file(c"/nonexistent.json".as_ptr(), 0, &mut solver);
+            assert_eq!(status, RbStatus::InvalidDictionary);
+            assert!(solver.is_null());
+        }
+    }
+
+    #[test]
+    fn test_header_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/rustybovich.h"));
+        let checked_in = include_str!("../include/rustybovich.h");
+        assert!(
+            generated == checked_in,
+            "include/rustybovich.h is out of date, regenerate it with \
+             `cbindgen --output include/rustybovich.h`"
+        );
+    }
+}
@@ -1,30 +1,45 @@
-use std::str::FromStr;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    guesser::GuesserWrapper,
-    words::{Pattern, PatternCache, Word},
+    guesser::{
+        option_values, rank_within, CancellationToken, Guesser, GuesserError, GuesserRegistry,
+        RankBudget,
+    },
+    keyboard::{Keyboard, LetterState},
+    line_editor::{Expecting, LineEditor},
+    opening_book::OpeningBook,
+    partition::Partition,
+    session::{History, Mode, SessionError, SolverSession, Step},
+    share,
+    words::{description_colors, is_description_color, LetterType, Pattern, PatternCache, Word},
+    Dictionary,
 };
 use colored::*;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone)]
-enum Mode {
-    Hard,
-    Normal,
-}
+mod json_rpc;
+mod tui;
 
-impl std::fmt::Display for Mode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            Mode::Hard => "hard".red(),
-            Mode::Normal => "norm".green(),
-        };
-        write!(f, "{s}")
+fn mode_label(mode: Mode) -> ColoredString {
+    match mode {
+        Mode::Hard => "hard".red(),
+        Mode::Normal => "norm".green(),
     }
 }
 
 #[derive(PartialEq, Clone)]
-enum Command {
+enum Command<const N: usize> {
     Next,
     Exit,
     Clear,
@@ -32,39 +47,439 @@ enum Command {
     ShowGuesses,
     Mode(Mode),
     Undo,
+    Redo,
+    History,
+    Drop(usize),
+    Save(PathBuf),
+    Load(PathBuf),
+    Grid {
+        answer: Word<N>,
+        rows: Vec<[LetterType; N]>,
+    },
+    Split(Word<N>),
+    Compare(Vec<Word<N>>),
+    Why(Word<N>),
+    Keys,
+    Ban(Vec<Word<N>>),
+    Allow(Vec<Word<N>>),
+    Share {
+        counts: bool,
+        transcript: Option<PathBuf>,
+    },
     Guess,
-    PatternDescription { word: String, colors: String },
+    Patterns(Vec<Pattern<N>>),
+}
+
+/// Error in a line of guesses, pointing at the `column`-th char of the line.
+#[derive(Debug, PartialEq)]
+struct EntryError {
+    column: usize,
+    message: String,
+}
+
+impl EntryError {
+    fn new(line: &str, byte: usize, message: impl ToString) -> Self {
+        Self {
+            column: line[..byte].chars().count(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Non-empty parts of `s` between the `separator` chars, with their byte offsets.
+fn split_with_offsets(s: &str, separator: impl Fn(char) -> bool) -> Vec<(usize, &str)> {
+    let mut parts = vec![];
+    let mut start = 0;
+    for (i, c) in s.char_indices().chain(std::iter::once((s.len(), ' '))) {
+        if i == s.len() || separator(c) {
+            if start < i {
+                parts.push((start, &s[start..i]));
+            }
+            start = i + c.len_utf8();
+        }
+    }
+    parts
+}
+
+/// Parses guesses entered on one line as `crane ..y.g` or `crane=..y.g`, several of them
+/// separated by `;`.
+fn parse_entries<const N: usize>(
+    line: &str,
+    valid: &[Word<N>],
+) -> Result<Vec<Pattern<N>>, EntryError> {
+    let mut patterns = vec![];
+    for (entry_start, entry) in split_with_offsets(line, |c| c == ';') {
+        let tokens = split_with_offsets(entry, |c| c.is_whitespace() || c == '=');
+        let (word_start, word, colors_start, colors) = match tokens[..] {
+            [] => continue,
+            [(word_start, word)] => {
+                let end = entry_start + word_start + word.len();
+                return Err(EntryError::new(
+                    line,
+                    end,
+                    "expecting colors after the word",
+                ));
+            }
+            [(word_start, word), (colors_start, colors)] => (
+                entry_start + word_start,
+                word,
+                entry_start + colors_start,
+                colors,
+            ),
+            [_, _, (extra_start, _), ..] => {
+                let message = "unexpected, separate guesses with `;`";
+                return Err(EntryError::new(line, entry_start + extra_start, message));
+            }
+        };
+
+        if let Some((i, _)) = word.char_indices().find(|(_, c)| !c.is_alphabetic()) {
+            return Err(EntryError::new(line, word_start + i, "not a letter"));
+        }
+        if let Some((i, _)) = colors
+            .char_indices()
+            .find(|&(_, c)| !is_description_color(c))
+        {
+            let message = "expecting a color: g, y or .";
+            return Err(EntryError::new(line, colors_start + i, message));
+        }
+
+        let length_error = |start: usize, s: &str, count: usize, what: &str| match s
+            .char_indices()
+            .filter(|&(_, c)| c != '\u{fe0f}')
+            .nth(N)
+        {
+            Some((i, _)) => EntryError::new(line, start + i, format!("more than {N} {what}")),
+            None => EntryError::new(
+                line,
+                start + s.len(),
+                format!("expecting {N} {what}, found {count}"),
+            ),
+        };
+        let word_length = word.chars().count();
+        if word_length != N {
+            return Err(length_error(word_start, word, word_length, "letters"));
+        }
+        let colors_length = description_colors(colors).count();
+        if colors_length != N {
+            return Err(length_error(colors_start, colors, colors_length, "colors"));
+        }
+
+        let pattern = Pattern::from_description(word, colors).unwrap();
+        if !valid.contains(&pattern.word()) {
+            let message = "no such word in the dictionary";
+            return Err(EntryError::new(line, word_start, message));
+        }
+        patterns.push(pattern);
+    }
+    Ok(patterns)
 }
 
+/// A saved step, guesses are saved the same way as by older versions.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SavedStep {
+    Guess { word: String, colors: String },
+    Ban { ban: Vec<String> },
+    Allow { allow: Vec<String> },
+}
+
+impl<const N: usize> From<&Step<N>> for SavedStep {
+    fn from(step: &Step<N>) -> Self {
+        match step {
+            Step::Guess(pattern) => SavedStep::Guess {
+                word: pattern.word().to_string(),
+                colors: pattern.to_description(),
+            },
+            Step::Ban(words) => SavedStep::Ban {
+                ban: words.iter().map(Word::to_string).collect(),
+            },
+            Step::Allow(words) => SavedStep::Allow {
+                allow: words.iter().map(Word::to_string).collect(),
+            },
+        }
+    }
+}
+
+/// Why `word` can't be the answer after `steps`, starting from `answers`, as the step that
+/// eliminated it; `None` if it is still possible.
+fn elimination<const N: usize>(
+    word: &Word<N>,
+    answers: &[Word<N>],
+    steps: &[Step<N>],
+    lies: usize,
+) -> Option<String> {
+    let mut eliminated = (!answers.contains(word))
+        .then(|| format!("{word} isn't among the answers of the dictionary"));
+    // allowing the word again doesn't undo a guess ruling it out
+    let mut by_guess = false;
+
+    for (i, step) in steps.iter().enumerate() {
+        match step {
+            Step::Guess(pattern) if eliminated.is_none() => {
+                let reason = match lies {
+                    0 => word.mismatch(pattern).map(|mismatch| mismatch.to_string()),
+                    lies => {
+                        let truth = Pattern::from_guess(&pattern.word(), word);
+                        let distance = truth.distance(pattern);
+                        (distance != lies).then(|| {
+                            format!(
+                                "it would get {truth}, {distance} colors differ instead of {lies}"
+                            )
+                        })
+                    }
+                };
+                by_guess = reason.is_some();
+                eliminated = reason.map(|reason| format!("{}. {pattern}: {word} {reason}", i + 1));
+            }
+            Step::Ban(words) if eliminated.is_none() && words.contains(word) => {
+                eliminated = Some(format!("{}. {word} was banned", i + 1));
+            }
+            Step::Allow(words) if !by_guess && words.contains(word) => eliminated = None,
+            _ => {}
+        }
+    }
+    eliminated
+}
+
+/// Game state written by `:save`, replayed on `:load`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub dictionary: PathBuf,
+    pub guesser: String,
+    /// Values of the guesser's options by name, missing in games saved before they were.
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+    pub lies: usize,
+    mode: Mode,
+    history: Vec<SavedStep>,
+}
+
+impl SavedGame {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(&path)?;
+        let reader = BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    fn steps<const N: usize>(&self) -> Result<Vec<Step<N>>, Box<dyn Error>> {
+        let words = |words: &[String]| {
+            words
+                .iter()
+                .map(|word| Word::from_str(word))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let steps = self
+            .history
+            .iter()
+            .map(|step| match step {
+                SavedStep::Guess { word, colors } => {
+                    Pattern::from_description(word, colors).map(Step::Guess)
+                }
+                SavedStep::Ban { ban } => words(ban).map(Step::Ban),
+                SavedStep::Allow { allow } => words(allow).map(Step::Allow),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(steps)
+    }
+}
+
+/// Ranking interrupted by Ctrl-C, if one is running.
+static RUNNING_RANKING: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(200);
+
+const COMMANDS: &[&str] = &[
+    ":next", ":exit", ":clear", ":show", ":showg", ":hard", ":norm", ":undo", ":redo", ":history",
+    ":guess", ":drop", ":save", ":load", ":share", ":grid", ":split", ":compare", ":why", ":keys",
+    ":ban", ":allow",
+];
+
 pub struct Game<const N: usize> {
-    valid: Vec<Word<N>>,
-    answers: Vec<Word<N>>,
-    guesser: GuesserWrapper,
+    dictionary: Dictionary<N>,
+    guessers: GuesserRegistry<N>,
+    guesser: &'static str,
+    time_limit: Option<Duration>,
+    opening_book: Option<OpeningBook<N>>,
+    lies: usize,
+    dictionary_path: Option<PathBuf>,
+    resumed: Option<SavedGame>,
+    history_file: Option<PathBuf>,
+    /// Set up by the first line read, so that the other front-ends don't touch the terminal.
+    line_editor: RefCell<Option<LineEditor<N>>>,
 }
 
 impl<const N: usize> Game<N> {
-    pub fn new(valid: Vec<Word<N>>, answers: Vec<Word<N>>, guesser: GuesserWrapper) -> Self {
-        Self {
-            valid,
-            answers,
+    pub fn new(
+        dictionary: Dictionary<N>,
+        guessers: GuesserRegistry<N>,
+        guesser: &str,
+    ) -> Result<Self, GuesserError> {
+        let guesser = guessers.get(guesser)?.name();
+
+        Ok(Self {
+            dictionary,
+            guessers,
             guesser,
+            time_limit: None,
+            opening_book: None,
+            lies: 0,
+            dictionary_path: None,
+            resumed: None,
+            history_file: None,
+            line_editor: RefCell::new(None),
+        })
+    }
+
+    /// Stop ranking guesses after `time_limit` and show the best ones found so far.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Take the first two guesses from `opening_book` instead of ranking them, if the book was
+    /// built for this dictionary and guesser.
+    pub fn with_opening_book(mut self, opening_book: OpeningBook<N>) -> Self {
+        self.opening_book = Some(opening_book);
+        self
+    }
+
+    /// Solve games where exactly `lies` letters of every pattern are colored wrong, as in Fibble.
+    pub fn with_lies(mut self, lies: usize) -> Self {
+        self.lies = lies;
+        self
+    }
+
+    /// Path of the dictionary, saved by `:save` for `--resume`.
+    pub fn with_dictionary_path(mut self, dictionary_path: PathBuf) -> Self {
+        self.dictionary_path = Some(dictionary_path);
+        self
+    }
+
+    /// Keep the history of the REPL input in `history_file` between runs.
+    pub fn with_history_file(mut self, history_file: PathBuf) -> Self {
+        self.history_file = Some(history_file);
+        self
+    }
+
+    /// Start the first game from a saved one.
+    pub fn with_resumed(mut self, saved: SavedGame) -> Self {
+        self.resumed = Some(saved);
+        self
+    }
+
+    fn save(&self, path: &Path, session: &SolverSession<N>) -> Result<(), Box<dyn Error>> {
+        let saved = SavedGame {
+            dictionary: self
+                .dictionary_path
+                .clone()
+                .ok_or("the dictionary path is unknown")?,
+            guesser: self.guesser.to_string(),
+            options: option_values(self.guesser()),
+            lies: self.lies,
+            mode: session.mode(),
+            history: session
+                .history()
+                .steps()
+                .iter()
+                .map(SavedStep::from)
+                .collect(),
+        };
+        saved.to_file(path)
+    }
+
+    fn load(&self, path: &Path, session: &mut SolverSession<N>) -> Result<(), Box<dyn Error>> {
+        let saved = SavedGame::from_file(path)?;
+        self.restore(&saved, session)
+    }
+
+    /// Replaces the game of `session` with `saved`, if it was played with the same dictionary
+    /// and lies.
+    fn restore(
+        &self,
+        saved: &SavedGame,
+        session: &mut SolverSession<N>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.dictionary_path.as_ref() != Some(&saved.dictionary) {
+            let dictionary = saved.dictionary.display();
+            Err(format!(
+                "the game uses dictionary {dictionary}, resume it with --resume"
+            ))?;
+        }
+        if saved.lies != self.lies {
+            Err(format!(
+                "the game has {} lies, resume it with --resume",
+                saved.lies
+            ))?;
         }
+        if saved.guesser != self.guesser {
+            println!("ranking with {} instead of {}", self.guesser, saved.guesser);
+        } else if saved.options != option_values(self.guesser()) {
+            let options = |options: &BTreeMap<String, String>| {
+                options
+                    .iter()
+                    .map(|(name, value)| format!("{name}={value}"))
+                    .join(" ")
+            };
+            println!(
+                "ranking with {} instead of {}",
+                options(&option_values(self.guesser())),
+                options(&saved.options)
+            );
+        }
+
+        session.restore(saved.mode, saved.steps()?);
+        Ok(())
+    }
+
+    fn opening_book_guess(&self, history: &History<N>) -> Option<(Word<N>, f32)> {
+        self.opening_book
+            .as_ref()
+            .filter(|_| self.lies == 0 && !history.changes_dictionary())
+            .filter(|book| {
+                book.is_for(
+                    &self.dictionary.valid,
+                    &self.dictionary.answers,
+                    self.guesser,
+                    self.guesser(),
+                )
+            })
+            .and_then(|book| book.guess(&history.patterns()))
+    }
+
+    fn guesser(&self) -> &dyn Guesser<N> {
+        self.guessers.get(self.guesser).unwrap().guesser()
     }
 
-    fn game(&self, pattern_cache: &PatternCache<N>) -> Command {
-        let mut possible_answers = self.answers.clone();
-        let mut possible_answers_bk = possible_answers.clone();
+    fn game(&self, session: &mut SolverSession<N>, resumed: Option<SavedGame>) -> Command<N> {
+        session.reset();
+        if let Some(saved) = resumed {
+            if let Err(e) = self.restore(&saved, session) {
+                println!("couldn't resume the game: {e}");
+            }
+        }
+
         let mut ranked_guesses = vec![];
-        let mut mode = Mode::Normal;
 
         loop {
             let command = loop {
+                let valid = session.valid();
+                let lies = match self.lies {
+                    0 => String::new(),
+                    lies => format!(" [lies: {lies}]"),
+                };
                 println!(
-                    "[{mode}] | {} words left | {}:",
-                    possible_answers.len(),
+                    "[{}]{lies} | {} words left | {}:",
+                    mode_label(session.mode()),
+                    session.candidates().len(),
                     "pattern or command".cyan()
                 );
-                match self.read_command() {
+                match self.read_command(&valid) {
                     c @ (Command::Next | Command::Exit) => return c,
                     Command::Clear => {
                         let _ = std::process::Command::new("clear").status().unwrap();
@@ -72,41 +487,93 @@ impl<const N: usize> Game<N> {
                     Command::Show => {
                         println!(
                             "{} words left:\n[{}]",
-                            possible_answers.len(),
-                            Self::word_list_to_string(&possible_answers)
+                            session.candidates().len(),
+                            Self::word_list_to_string(session.candidates())
                         );
                     }
                     Command::ShowGuesses => {
-                        Self::show_guesses(&ranked_guesses, &possible_answers, 10)
+                        Self::show_guesses(&ranked_guesses, session.candidates(), 10);
+                    }
+                    Command::Mode(mode) => session.set_mode(mode),
+                    Command::Undo => match session.undo() {
+                        Some(step) => println!("undone {step}"),
+                        None => println!("nothing to undo"),
+                    },
+                    Command::Redo => match session.redo() {
+                        Some(step) => println!("redone {step}"),
+                        None => println!("nothing to redo"),
+                    },
+                    Command::History => Self::show_history(session),
+                    Command::Drop(n) => match session.drop(n) {
+                        Some(step) => println!("dropped {step}"),
+                        None => println!("no step {n}, see {}", ":history".cyan()),
+                    },
+                    Command::Save(path) => match self.save(&path, session) {
+                        Ok(()) => println!("saved to {}", path.display()),
+                        Err(e) => println!("couldn't save the game: {e}"),
+                    },
+                    Command::Load(path) => match self.load(&path, session) {
+                        Ok(()) => {
+                            ranked_guesses = vec![];
+                            println!("loaded {}", path.display());
+                        }
+                        Err(e) => println!("couldn't load the game: {e}"),
+                    },
+                    Command::Grid { answer, rows } => self.show_grid(&answer, &rows),
+                    Command::Split(guess) => Self::show_split(&session.split(&guess)),
+                    Command::Why(word) => self.show_why(&word, session.history()),
+                    Command::Keys => self.show_keys(session.history()),
+                    Command::Ban(words) => {
+                        let not_possible = session.ban(words);
+                        if !not_possible.is_empty() {
+                            let words = Self::word_list_to_string(&not_possible);
+                            println!("not among the words left: {words}");
+                        }
                     }
-                    Command::Mode(m) => mode = m,
-                    Command::Undo => possible_answers = possible_answers_bk.clone(),
+                    Command::Allow(words) => session.allow(words),
+                    Command::Compare(guesses) => self.compare(&guesses, session),
+                    Command::Share {
+                        counts,
+                        transcript: None,
+                    } => Self::share(session, counts),
+                    Command::Share {
+                        counts: _,
+                        transcript: Some(path),
+                    } => match self.write_transcript(&path, session) {
+                        Ok(()) => println!("transcript written to {}", path.display()),
+                        Err(e) => println!("couldn't write the transcript: {e}"),
+                    },
                     c @ Command::Guess => break c,
-                    c @ Command::PatternDescription { word: _, colors: _ } => break c,
+                    c @ Command::Patterns(_) => break c,
                 }
             };
 
             match command {
                 Command::Guess => {
-                    let valid_guesses = match mode {
-                        Mode::Hard => &possible_answers,
-                        Mode::Normal => &self.valid,
+                    let book_guess = match session.mode() {
+                        Mode::Hard => None,
+                        Mode::Normal => self.opening_book_guess(session.history()),
                     };
-                    ranked_guesses =
-                        self.guesser
-                            .rank_guesses(valid_guesses, &possible_answers, pattern_cache);
-                    Self::show_guesses(&ranked_guesses, &possible_answers, 10);
+
+                    if let Some((word, rank)) = book_guess {
+                        println!("{}", "from the opening book".yellow());
+                        ranked_guesses = vec![(word, rank)];
+                        Self::show_guesses(&ranked_guesses, session.candidates(), 10);
+                    } else {
+                        ranked_guesses = self.rank(session);
+                    }
 
                     println!();
                 }
-                Command::PatternDescription { word, colors } => {
-                    let pattern = Pattern::from_description(&word, &colors).unwrap();
-
-                    possible_answers_bk.clone_from(&possible_answers);
-                    possible_answers = pattern.filter_words(&possible_answers);
+                Command::Patterns(patterns) => {
+                    for pattern in patterns {
+                        if let Err(e) = session.apply(pattern) {
+                            println!("{e}");
+                        }
+                    }
 
-                    if possible_answers.len() == 1 {
-                        println!("answer: {}", format!("{}", possible_answers[0]).red());
+                    if let [answer] = session.candidates() {
+                        println!("answer: {}", format!("{answer}").red());
                     }
                 }
                 _ => unreachable!(),
@@ -114,14 +581,402 @@ impl<const N: usize> Game<N> {
         }
     }
 
+    fn show_history(session: &SolverSession<N>) {
+        let steps = session.history().steps();
+        if steps.is_empty() {
+            println!("no steps yet");
+            return;
+        }
+
+        for (i, (step, left)) in std::iter::zip(steps, session.replay()).enumerate() {
+            println!("{}. {step} | {} words left", i + 1, left.len());
+        }
+    }
+
+    fn show_grid(&self, answer: &Word<N>, rows: &[[LetterType; N]]) {
+        const SHOW_N: usize = 10;
+
+        let guesses = share::analyse_grid(rows, answer, &self.dictionary.valid);
+        for (row, guesses) in std::iter::zip(rows, guesses) {
+            let more = match guesses.len() {
+                n if n > SHOW_N => format!(" and {} more", n - SHOW_N),
+                _ => String::new(),
+            };
+            println!(
+                "{} {} words: [{}]{more}",
+                share::row_to_emoji(row),
+                guesses.len(),
+                Self::word_list_to_string(&guesses[..guesses.len().min(SHOW_N)])
+            );
+        }
+    }
+
+    fn show_split(partition: &Partition<N>) {
+        const SHOW_N: usize = 10;
+
+        for (pattern, bucket) in &partition.buckets {
+            let more = match bucket.len() {
+                n if n > SHOW_N => format!(" and {} more", n - SHOW_N),
+                _ => String::new(),
+            };
+            println!(
+                "{pattern} {}: [{}]{more}",
+                bucket.len(),
+                Self::word_list_to_string(&bucket[..bucket.len().min(SHOW_N)])
+            );
+        }
+        println!(
+            "{} buckets, largest {}, {:.2} words left on average",
+            partition.buckets.len(),
+            partition.largest(),
+            partition.expected_size()
+        );
+    }
+
+    /// Prints the step that eliminated `word` and why.
+    fn show_why(&self, word: &Word<N>, history: &History<N>) {
+        let eliminated = elimination(word, &self.dictionary.answers, history.steps(), self.lies);
+        match eliminated {
+            Some(message) => println!("{message}"),
+            None => println!("{word} is still possible"),
+        }
+    }
+
+    /// Prints the keyboard colored by the guesses so far, then what is known about the letters
+    /// in the answer. With lies no color is certain, so the keys are left uncolored.
+    fn show_keys(&self, history: &History<N>) {
+        let mut keyboard = Keyboard::for_words(&self.dictionary.valid).with_lies(self.lies);
+        keyboard.set_patterns(&history.patterns());
+        if self.lies > 0 {
+            println!("any color may be a lie, no letter is known for sure");
+        }
+
+        for (i, row) in keyboard.rows().iter().enumerate() {
+            let keys = row
+                .iter()
+                .map(|&letter| {
+                    let key = format!(" {} ", letter.to_uppercase());
+                    match keyboard.letter(letter).map(LetterState::color) {
+                        Some(LetterType::Green) => key.black().on_green(),
+                        Some(LetterType::Yellow) => key.black().on_yellow(),
+                        Some(LetterType::Gray) => key.white().on_bright_black(),
+                        None => key.normal(),
+                    }
+                })
+                .join("");
+            println!("{}{keys}", " ".repeat(i));
+        }
+
+        let positions = |positions: &[usize]| positions.iter().map(|p| p + 1).join(", ");
+        for (letter, state) in keyboard.letters().filter(|(_, state)| state.min_count > 0) {
+            let mut known = vec![match state.max_count {
+                Some(max) if max == state.min_count => format!("exactly {max}"),
+                Some(max) => format!("{} to {max}", state.min_count),
+                None => format!("at least {}", state.min_count),
+            }];
+            if !state.green.is_empty() {
+                known.push(format!("at {}", positions(&state.green)));
+            }
+            if !state.forbidden.is_empty() {
+                known.push(format!("not at {}", positions(&state.forbidden)));
+            }
+            println!("{letter}: {}", known.join(", "));
+        }
+    }
+
+    /// Prints a table of `guesses` ranked by every guesser and by the partition metrics, with
+    /// the best value in each column highlighted. The rankings stop at the time limit or on
+    /// Ctrl-C, the guesses not ranked by then are shown as `-`. The partition metrics don't
+    /// account for lies, so they are left out when there are any.
+    fn compare(&self, guesses: &[Word<N>], session: &SolverSession<N>) {
+        let possible_answers = session.candidates();
+        if possible_answers.is_empty() {
+            println!("{}", SessionError::NoCandidates);
+            return;
+        }
+
+        let valid_guesses = session.valid_guesses();
+        let (pattern_cache, lies) = (session.pattern_cache(), self.lies);
+        let budget = self.start_ranking();
+        let mut complete = true;
+        // header, values for every guess, whether higher is better
+        let mut columns: Vec<(String, Vec<f32>, bool)> = self
+            .guessers
+            .iter()
+            .map(|entry| {
+                let ranking = rank_within(guesses, &budget, &mut |_| {}, |guess| {
+                    entry.guesser().rank_guess_within(
+                        guess,
+                        &valid_guesses,
+                        possible_answers,
+                        pattern_cache,
+                        lies,
+                        &budget,
+                    )
+                });
+                complete &= ranking.complete;
+                let ranks = guesses
+                    .iter()
+                    .map(|guess| {
+                        ranking
+                            .guesses
+                            .iter()
+                            .find(|(ranked, _)| ranked == guess)
+                            .map_or(f32::NAN, |(_, rank)| *rank)
+                    })
+                    .collect();
+                (entry.name().to_string(), ranks, false)
+            })
+            .collect();
+        Self::finish_ranking();
+
+        if !complete {
+            println!(
+                "{}",
+                "not every guess was ranked (Ctrl-C or time limit)".yellow()
+            );
+        }
+        if self.lies == 0 {
+            let partitions = guesses
+                .iter()
+                .map(|guess| Partition::new(guess, possible_answers))
+                .collect_vec();
+            let metric = |f: fn(&Partition<N>) -> f32| partitions.iter().map(f).collect_vec();
+            columns.extend([
+                (
+                    "expected".to_string(),
+                    metric(Partition::expected_size),
+                    false,
+                ),
+                ("worst".to_string(), metric(|p| p.largest() as f32), false),
+                ("entropy".to_string(), metric(Partition::entropy), true),
+                (
+                    "buckets".to_string(),
+                    metric(|p| p.buckets.len() as f32),
+                    true,
+                ),
+                (
+                    "solve next".to_string(),
+                    metric(Partition::solve_probability),
+                    true,
+                ),
+            ]);
+        }
+
+        print!("{:N$}", "");
+        for (header, _, _) in &columns {
+            print!("  {header:>10}");
+        }
+        println!();
+        for (i, guess) in guesses.iter().enumerate() {
+            print!("{guess}");
+            for (_, values, higher_is_better) in &columns {
+                let best = values
+                    .iter()
+                    .copied()
+                    .reduce(|a, b| match higher_is_better {
+                        true => a.max(b),
+                        false => a.min(b),
+                    });
+                let cell = match values[i].is_nan() {
+                    true => format!("  {:>10}", "-"),
+                    false => format!("  {:>10.3}", values[i]),
+                };
+                match Some(values[i]) == best {
+                    true => print!("{}", cell.green().bold()),
+                    false => print!("{cell}"),
+                }
+            }
+            println!();
+        }
+    }
+
+    fn share_title(mode: Mode) -> String {
+        match mode {
+            Mode::Hard => "rustybovich*".to_string(),
+            Mode::Normal => "rustybovich".to_string(),
+        }
+    }
+
+    fn share(session: &SolverSession<N>, counts: bool) {
+        let history = session.history();
+        println!(
+            "{}",
+            share::share_block(&Self::share_title(session.mode()), &history.patterns())
+        );
+        if counts {
+            let answers_left = std::iter::zip(history.steps(), session.replay())
+                .filter(|(step, _)| matches!(step, Step::Guess(_)))
+                .map(|(_, left)| left.len())
+                .collect_vec();
+            println!("{}", share::counts_line(&answers_left));
+        }
+    }
+
+    /// Writes a Markdown transcript with the guesser's best guesses for every step, ranking
+    /// them again with the usual time limit.
+    fn write_transcript(
+        &self,
+        path: &Path,
+        session: &SolverSession<N>,
+    ) -> Result<(), Box<dyn Error>> {
+        const ALTERNATIVES_N: usize = 3;
+
+        let mode = session.mode();
+        let valid = session.valid();
+        let after = session.replay();
+        let before = std::iter::once(&self.dictionary.answers).chain(&after);
+        let steps = std::iter::zip(session.history().steps(), before.zip(&after))
+            .filter_map(|(step, answers)| match step {
+                Step::Guess(pattern) => Some((pattern, answers)),
+                _ => None,
+            })
+            .map(|(pattern, (possible_answers, left))| {
+                let valid_guesses = match mode {
+                    Mode::Hard => possible_answers,
+                    Mode::Normal => &valid,
+                };
+                let mut alternatives =
+                    self.rank_quietly(valid_guesses, possible_answers, session.pattern_cache());
+                alternatives.truncate(ALTERNATIVES_N);
+                share::TranscriptStep {
+                    pattern: *pattern,
+                    answers_left: left.len(),
+                    alternatives,
+                }
+            })
+            .collect_vec();
+
+        let markdown = share::transcript(&Self::share_title(mode), &steps);
+        std::fs::write(path, markdown)?;
+        Ok(())
+    }
+
+    /// Budget for a ranking: the time limit and `token`.
+    fn ranking_budget(&self, token: &CancellationToken) -> RankBudget {
+        let budget = RankBudget::unlimited().with_cancellation(token.clone());
+        match self.time_limit {
+            Some(time_limit) => budget.with_timeout(time_limit),
+            None => budget,
+        }
+    }
+
+    /// Budget for a ranking with a token that Ctrl-C cancels while it runs.
+    fn start_ranking(&self) -> RankBudget {
+        let token = CancellationToken::new();
+        let budget = self.ranking_budget(&token);
+        *RUNNING_RANKING.lock().unwrap() = Some(token);
+        budget
+    }
+
+    fn finish_ranking() {
+        *RUNNING_RANKING.lock().unwrap() = None;
+    }
+
+    /// Ranks guesses without showing them, nothing is ranked with no possible answers.
+    fn rank_quietly(
+        &self,
+        valid_guesses: &[Word<N>],
+        possible_answers: &[Word<N>],
+        pattern_cache: &PatternCache<N>,
+    ) -> Vec<(Word<N>, f32)> {
+        if possible_answers.is_empty() {
+            return vec![];
+        }
+
+        let budget = self.start_ranking();
+        let ranking = self.guesser().rank_guesses_within(
+            valid_guesses,
+            possible_answers,
+            pattern_cache,
+            self.lies,
+            &budget,
+            &mut |_| {},
+        );
+        Self::finish_ranking();
+        ranking.guesses
+    }
+
+    /// Ranks guesses showing a live top list; Ctrl-C stops the ranking early.
+    fn rank(&self, session: &SolverSession<N>) -> Vec<(Word<N>, f32)> {
+        let budget = self.start_ranking();
+
+        let total = session.valid_guesses().len();
+        let live = std::io::stdout().is_terminal();
+        let mut lines_shown = 0;
+        let mut last_redraw = Instant::now();
+        let ranking = session.rank(self.guesser(), &budget, &mut |guesses| {
+            if live && last_redraw.elapsed() >= REDRAW_INTERVAL {
+                Self::clear_lines(lines_shown);
+                println!("{}", Self::progress(guesses.len(), total));
+                lines_shown = 1 + Self::show_guesses(guesses, session.candidates(), 10);
+                last_redraw = Instant::now();
+            }
+        });
+
+        Self::finish_ranking();
+
+        Self::clear_lines(lines_shown);
+        let ranking = match ranking {
+            Ok(ranking) => ranking,
+            Err(e) => {
+                println!("{e}");
+                return vec![];
+            }
+        };
+        if !ranking.complete {
+            println!(
+                "{} (Ctrl-C or time limit)",
+                Self::progress(ranking.guesses.len(), total)
+            );
+        }
+        Self::show_guesses(&ranking.guesses, session.candidates(), 10);
+
+        ranking.guesses
+    }
+
+    fn progress(ranked: usize, total: usize) -> ColoredString {
+        format!("ranked {ranked} of {total} guesses").yellow()
+    }
+
+    fn clear_lines(n: usize) {
+        if n > 0 {
+            print!("\x1b[{n}A\x1b[J");
+        }
+    }
+
     pub fn run(&self) {
-        let pattern_cache = Pattern::prepare_all(&self.valid, &self.answers);
-        while Command::Next == self.game(&pattern_cache) {}
+        // ignore the error if the handler has been set by an earlier game
+        let _ = ctrlc::set_handler(|| match RUNNING_RANKING.lock().unwrap().as_ref() {
+            Some(token) => token.cancel(),
+            None => std::process::exit(130),
+        });
+
+        let mut session = SolverSession::new(&self.dictionary, self.lies).with_prepared_patterns();
+        let mut resumed = self.resumed.clone();
+        while Command::Next == self.game(&mut session, resumed.take()) {}
+    }
+
+    /// The next line of input, exits on Ctrl-C or Ctrl-D.
+    fn read_line(&self, expecting: Expecting) -> String {
+        let mut line_editor = self.line_editor.borrow_mut();
+        let line_editor = line_editor.get_or_insert_with(|| {
+            LineEditor::new(COMMANDS, &self.dictionary.valid, self.history_file.clone())
+                .expect("couldn't set up the input")
+        });
+        match line_editor.read_line(expecting) {
+            Some(line) => line,
+            None => std::process::exit(0),
+        }
     }
 
-    fn read_command(&self) -> Command {
-        let line = std::io::stdin().lines().next().unwrap().unwrap();
-        match line.as_str() {
+    fn read_command(&self, valid: &[Word<N>]) -> Command<N> {
+        let line = self.read_line(Expecting::Command);
+        let (name, argument) = match line.split_once(' ') {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (line.as_str(), ""),
+        };
+        match name {
             ":next" => Command::Next,
             ":exit" => Command::Exit,
             ":clear" => Command::Clear,
@@ -130,13 +985,133 @@ impl<const N: usize> Game<N> {
             ":hard" => Command::Mode(Mode::Hard),
             ":norm" => Command::Mode(Mode::Normal),
             ":undo" => Command::Undo,
+            ":redo" => Command::Redo,
+            ":history" => Command::History,
             "" | ":guess" => Command::Guess,
+            ":drop" => match argument.parse() {
+                Ok(n) => Command::Drop(n),
+                Err(_) => {
+                    println!("expecting a step number, e.g. {}", ":drop 2".cyan());
+                    self.read_command(valid)
+                }
+            },
+            ":save" | ":load" if argument.is_empty() => {
+                println!(
+                    "expecting a file, e.g. {}",
+                    format!("{name} game.json").cyan()
+                );
+                self.read_command(valid)
+            }
+            ":save" => Command::Save(argument.into()),
+            ":share" => Command::Share {
+                counts: argument == "counts",
+                transcript: match argument {
+                    "" | "counts" => None,
+                    path => Some(path.into()),
+                },
+            },
+            ":load" => Command::Load(argument.into()),
+            ":split" => match Word::from_str(argument) {
+                Ok(guess) if valid.contains(&guess) => Command::Split(guess),
+                Ok(_) => {
+                    println!("no such word in the dictionary");
+                    self.read_command(valid)
+                }
+                Err(_) => {
+                    println!("expecting a guess, e.g. {}", ":split crate".cyan());
+                    self.read_command(valid)
+                }
+            },
+            ":ban" | ":allow" => {
+                let words: Result<Vec<Word<N>>, _> =
+                    argument.split_whitespace().map(Word::from_str).collect();
+                match words {
+                    Ok(words) if !words.is_empty() && name == ":ban" => Command::Ban(words),
+                    Ok(words) if !words.is_empty() && words.iter().all(Word::is_alphabetic) => {
+                        Command::Allow(words)
+                    }
+                    _ => {
+                        println!(
+                            "expecting words of {N} letters, e.g. {}",
+                            format!("{name} crate").cyan()
+                        );
+                        self.read_command(valid)
+                    }
+                }
+            }
+            ":keys" => Command::Keys,
+            ":why" => match Word::from_str(argument) {
+                Ok(word) => Command::Why(word),
+                Err(_) => {
+                    println!("expecting a word, e.g. {}", ":why crate".cyan());
+                    self.read_command(valid)
+                }
+            },
+            ":compare" => {
+                let guesses: Result<Vec<Word<N>>, _> =
+                    argument.split_whitespace().map(Word::from_str).collect();
+                match guesses {
+                    Ok(guesses) if guesses.len() < 2 => {
+                        println!(
+                            "expecting two or more guesses, e.g. {}",
+                            ":compare crane slate".cyan()
+                        );
+                        self.read_command(valid)
+                    }
+                    Ok(guesses) => match guesses.iter().find(|g| !valid.contains(g)) {
+                        Some(guess) => {
+                            println!("no such word in the dictionary: {guess}");
+                            self.read_command(valid)
+                        }
+                        None => Command::Compare(guesses),
+                    },
+                    Err(_) => {
+                        println!("expecting guesses of {N} letters");
+                        self.read_command(valid)
+                    }
+                }
+            }
+            ":grid" => match Word::from_str(argument) {
+                Ok(answer) => {
+                    println!("paste the shared grid, then an empty line:");
+                    let rows = std::iter::repeat_with(|| self.read_line(Expecting::GridRow))
+                        .scan(vec![], |rows, line| {
+                            if line.trim().is_empty() && !rows.is_empty() {
+                                return None;
+                            }
+                            rows.extend(share::parse_row(&line));
+                            Some(rows.clone())
+                        })
+                        .last()
+                        .unwrap_or_default();
+                    Command::Grid { answer, rows }
+                }
+                Err(_) => {
+                    println!("expecting the answer, e.g. {}", ":grid crate".cyan());
+                    self.read_command(valid)
+                }
+            },
+            _ if name.starts_with(':') => {
+                println!("unknown command {}", name.red());
+                self.read_command(valid)
+            }
+            _ if line.contains([' ', '=', ';']) => match parse_entries(&line, valid) {
+                Ok(patterns) => Command::Patterns(patterns),
+                Err(EntryError { column, message }) => {
+                    println!(
+                        "{line}\n{}{}",
+                        " ".repeat(column),
+                        format!("^ {message}").red()
+                    );
+                    self.read_command(valid)
+                }
+            },
             _ => {
                 let word = std::iter::once(line)
-                    .chain(std::io::stdin().lines().map(Result::unwrap))
+                    .chain(std::iter::repeat_with(|| self.read_line(Expecting::Word)))
                     .find(|word| match word.chars().count() {
                         n if n == N => {
-                            if self.valid.contains(&Word::from_str(word).unwrap()) {
+                            if valid.contains(&Word::from_str(word).unwrap()) {
                                 true
                             } else {
                                 println!("no such word in the dictionary");
@@ -150,10 +1125,8 @@ impl<const N: usize> Game<N> {
                     })
                     .unwrap();
 
-                let colors = std::io::stdin()
-                    .lines()
-                    .map(Result::unwrap)
-                    .find(|word| match word.chars().count() {
+                let colors = std::iter::repeat_with(|| self.read_line(Expecting::Colors))
+                    .find(|colors| match description_colors(colors).count() {
                         n if n == N => true,
                         n => {
                             println!("expecting {} colors, found {}", N, n);
@@ -162,7 +1135,7 @@ impl<const N: usize> Game<N> {
                     })
                     .unwrap();
 
-                Command::PatternDescription { word, colors }
+                Command::Patterns(vec![Pattern::from_description(&word, &colors).unwrap()])
             }
         }
     }
@@ -171,15 +1144,20 @@ impl<const N: usize> Game<N> {
         words.iter().map(|w| format!("{}", w)).join(", ")
     }
 
-    fn show_guesses(sorted_guesses: &[(Word<N>, f32)], words_left: &[Word<N>], show_n: usize) {
+    /// Prints the best of `sorted_guesses`, returns the number of lines printed.
+    fn show_guesses(
+        sorted_guesses: &[(Word<N>, f32)],
+        words_left: &[Word<N>],
+        show_n: usize,
+    ) -> usize {
         if sorted_guesses.is_empty() {
             println!("couldn't make any guesses");
-            return;
+            return 1;
         }
 
         if words_left.is_empty() {
             println!("no possible words left");
-            return;
+            return 1;
         }
 
         let threshold = 0.1;
@@ -211,7 +1189,7 @@ impl<const N: usize> Game<N> {
                     prev_rank == rank_int
                 }
             })
-            .for_each(|(word, rank, left)| {
+            .map(|(word, rank, left)| {
                 let word_str = if left {
                     word.to_string().green()
                 } else {
@@ -219,6 +1197,114 @@ impl<const N: usize> Game<N> {
                 };
 
                 println!("{word_str}: {rank:.2}");
-            });
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    fn pattern(word: &str, colors: &str) -> Pattern<5> {
+        Pattern::from_description(word, colors).unwrap()
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let valid = words(&["crane", "light", "below"]);
+        let error = |column, message: &str| {
+            Err(EntryError {
+                column,
+                message: message.to_string(),
+            })
+        };
+
+        assert_eq!(
+            parse_entries("crane ..y.g", &valid),
+            Ok(vec![pattern("crane", "..y.g")])
+        );
+        assert_eq!(
+            parse_entries(" crane=🟩⬛\u{fe0f}⬛⬛⬛; light  g.... ;", &valid),
+            Ok(vec![pattern("crane", "g...."), pattern("light", "g....")])
+        );
+
+        assert_eq!(
+            parse_entries("crane ..z.g", &valid),
+            error(8, "expecting a color: g, y or .")
+        );
+        assert_eq!(
+            parse_entries("cr4ne ..y.g", &valid),
+            error(2, "not a letter")
+        );
+        assert_eq!(
+            parse_entries("crane ..y.g;lights g....", &valid),
+            error(17, "more than 5 letters")
+        );
+        assert_eq!(
+            parse_entries("crane ..y", &valid),
+            error(9, "expecting 5 colors, found 3")
+        );
+        assert_eq!(
+            parse_entries("crane;", &valid),
+            error(5, "expecting colors after the word")
+        );
+        assert_eq!(
+            parse_entries("crane ..y.g light", &valid),
+            error(12, "unexpected, separate guesses with `;`")
+        );
+        assert_eq!(
+            parse_entries("crate ..y.g", &valid),
+            error(0, "no such word in the dictionary")
+        );
+    }
+
+    #[test]
+    fn test_elimination() {
+        let answers = words(&["crate", "trace", "below"]);
+        let steps = [
+            Step::Guess(pattern("below", ".y...")),
+            Step::Ban(words(&["trace"])),
+        ];
+        let elimination =
+            |word: &str, lies| elimination(&word.parse().unwrap(), &answers, &steps, lies);
+
+        assert_eq!(elimination("crate", 0), None);
+        assert!(elimination("below", 0).unwrap().starts_with("1. below"));
+        assert_eq!(elimination("trace", 0).unwrap(), "2. trace was banned");
+        assert_eq!(
+            elimination("elbow", 0).unwrap(),
+            "elbow isn't among the answers of the dictionary"
+        );
+        assert!(elimination("crate", 1).unwrap().starts_with("1. below"));
+
+        let steps = [steps[0].clone(), Step::Allow(words(&["below", "elbow"]))];
+        let allowed = |word: &str| super::elimination(&word.parse().unwrap(), &answers, &steps, 0);
+        // elbow was never an answer
+        assert!(allowed("below").unwrap().starts_with("1. below"));
+        assert_eq!(allowed("elbow"), None);
+    }
+
+    #[test]
+    fn test_saved_game_options() {
+        let saved: SavedGame = serde_json::from_str(
+            r#"{"dictionary": "en.json", "guesser": "bfs", "lies": 0, "mode": "normal",
+                "history": [{"word": "crane", "colors": "..y.g"}]}"#,
+        )
+        .unwrap();
+        assert!(saved.options.is_empty());
+        assert_eq!(
+            saved.steps::<5>().unwrap(),
+            [Step::Guess(pattern("crane", "..y.g"))]
+        );
+
+        let saved = SavedGame {
+            options: [("max_depth".to_string(), "4".to_string())].into(),
+            ..saved
+        };
+        let read: SavedGame =
+            serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        assert_eq!(read.options, saved.options);
     }
 }
@@ -0,0 +1,275 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use super::{Game, SavedStep};
+use crate::{
+    guesser::CancellationToken,
+    session::{Mode, SolverSession},
+    words::{Pattern, Word},
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The request is fine, but can't be done in the current game, e.g. there is nothing to undo.
+const GAME_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn default_top() -> usize {
+    10
+}
+
+#[derive(Deserialize)]
+struct RankParams {
+    guesser: Option<String>,
+    #[serde(default = "default_top")]
+    top: usize,
+    /// Rank only the possible answers, as in hard mode.
+    #[serde(default)]
+    hard: bool,
+}
+
+#[derive(Deserialize)]
+struct SplitParams<const N: usize> {
+    guess: Word<N>,
+}
+
+/// Game driven by JSON-RPC requests, one per line, instead of the REPL prompts.
+struct JsonRpc<'a, const N: usize> {
+    game: &'a Game<N>,
+    session: SolverSession<'a, N>,
+}
+
+impl<'a, const N: usize> JsonRpc<'a, N> {
+    fn new(game: &'a Game<N>) -> Self {
+        Self {
+            game,
+            session: SolverSession::new(&game.dictionary, game.lies).with_prepared_patterns(),
+        }
+    }
+
+    fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => self.respond(request),
+                Err(e) => error_response(Value::Null, RpcError::new(PARSE_ERROR, e)),
+            };
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn respond(&mut self, request: Value) -> Value {
+        let Request { id, method, params } = match serde_json::from_value(request) {
+            Ok(request) => request,
+            Err(e) => return error_response(Value::Null, RpcError::new(INVALID_REQUEST, e)),
+        };
+
+        match self.call(&method, params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        }
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "apply_pattern" => {
+                let pattern: Pattern<N> = parse_params(params)?;
+                self.session
+                    .apply(pattern)
+                    .map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+                Ok(self.result())
+            }
+            "candidates" => Ok(json!(self.session.candidates())),
+            "rank" => self.rank(parse_params(params)?),
+            "split" => {
+                let SplitParams { guess } = parse_params(params)?;
+                let partition = self.session.split(&guess);
+                let buckets: Vec<_> = partition
+                    .buckets
+                    .iter()
+                    .map(|(pattern, words)| {
+                        json!({ "colors": pattern.to_description(), "words": words })
+                    })
+                    .collect();
+                Ok(json!({
+                    "buckets": buckets,
+                    "largest": partition.largest(),
+                    "expected_size": partition.expected_size(),
+                    "entropy": partition.entropy(),
+                }))
+            }
+            "undo" => match self.session.undo() {
+                Some(step) => {
+                    let undone = json!(SavedStep::from(step));
+                    let mut result = self.result();
+                    result["undone"] = undone;
+                    Ok(result)
+                }
+                None => Err(RpcError::new(GAME_ERROR, "nothing to undo")),
+            },
+            "reset" => {
+                self.session.reset();
+                Ok(self.result())
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method `{method}`"),
+            )),
+        }
+    }
+
+    /// The result of the requests changing the game.
+    fn result(&self) -> Value {
+        json!({ "candidates": self.session.candidates().len() })
+    }
+
+    fn rank(&mut self, params: RankParams) -> Result<Value, RpcError> {
+        let name = params.guesser.as_deref().unwrap_or(self.game.guesser);
+        let entry = self
+            .game
+            .guessers
+            .get(name)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+
+        self.session.set_mode(match params.hard {
+            true => Mode::Hard,
+            false => Mode::Normal,
+        });
+        let ranking = self
+            .session
+            .rank(
+                entry.guesser(),
+                &self.game.ranking_budget(&CancellationToken::new()),
+                &mut |_| {},
+            )
+            .map_err(|e| RpcError::new(GAME_ERROR, e))?;
+
+        let guesses: Vec<_> = ranking
+            .guesses
+            .iter()
+            .take(params.top)
+            .map(|(word, rank)| json!({ "word": word, "rank": rank }))
+            .collect();
+        Ok(json!({
+            "guesser": entry.name(),
+            "complete": ranking.complete,
+            "guesses": guesses,
+        }))
+    }
+}
+
+/// Parses the params of a request, missing params are taken for an empty object.
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => json!({}),
+        params => params,
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+fn error_response(id: Value, RpcError { code, message }: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+impl<const N: usize> Game<N> {
+    /// Runs the game with JSON-RPC requests read from `input` a line at a time, writing a line
+    /// with the response to each to `output`. The methods are `apply_pattern`, `candidates`,
+    /// `rank`, `split`, `undo` and `reset`.
+    pub fn run_json_rpc(&self, input: impl BufRead, output: impl Write) -> io::Result<()> {
+        let mut json_rpc = JsonRpc::new(self);
+        if let Some(saved) = &self.resumed {
+            if let Err(e) = self.restore(saved, &mut json_rpc.session) {
+                eprintln!("couldn't resume the game: {e}");
+            }
+        }
+
+        json_rpc.run(input, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{guesser::GuesserRegistry, test_util::dictionary};
+
+    #[test]
+    fn test_requests() {
+        let dictionary = dictionary();
+        let game = Game::new(dictionary, GuesserRegistry::default(), "naive").unwrap();
+
+        let requests = [
+            r#"{"id": 1, "method": "apply_pattern", "params": {"word": "light", "colors": "y...."}}"#,
+            r#"{"id": 2, "method": "candidates"}"#,
+            r#"{"id": 3, "method": "split", "params": {"guess": "below"}}"#,
+            r#"{"id": 4, "method": "rank", "params": {"top": 1}}"#,
+            r#"{"id": 5, "method": "undo"}"#,
+            r#"{"id": 6, "method": "undo"}"#,
+            r#"{"id": 7, "method": "apply_pattern", "params": {"word": "xxxxx", "colors": "....."}}"#,
+            r#"{"id": 8, "method": "guess"}"#,
+            r#"{"id": 9"#,
+        ];
+        let mut output = vec![];
+        game.run_json_rpc(requests.join("\n").as_bytes(), &mut output)
+            .unwrap();
+        let responses: Vec<Value> = output
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+
+        assert_eq!(responses.len(), requests.len());
+        assert_eq!(responses[0]["result"], json!({ "candidates": 2 }));
+        assert_eq!(responses[1]["result"], json!(["below", "elbow"]));
+        assert_eq!(responses[2]["result"]["largest"], 1);
+        assert_eq!(
+            responses[3]["result"]["guesses"].as_array().unwrap().len(),
+            1
+        );
+        assert_eq!(
+            responses[4]["result"]["undone"],
+            json!({ "word": "light", "colors": "y...." })
+        );
+        assert_eq!(responses[4]["result"]["candidates"], 4);
+        assert_eq!(responses[5]["error"]["code"], GAME_ERROR);
+        assert_eq!(responses[6]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[7]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[8]["error"]["code"], PARSE_ERROR);
+        assert_eq!(responses[8]["id"], Value::Null);
+    }
+}
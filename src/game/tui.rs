@@ -0,0 +1,542 @@
+use std::{io, time::Duration};
+
+use itertools::Itertools;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use super::{parse_entries, EntryError, Game, SavedGame};
+use crate::{
+    guesser::CancellationToken,
+    keyboard::{Keyboard, LetterState},
+    session::{Mode, SolverSession, Step},
+    words::{LetterType, Word},
+};
+
+const KEYS_HELP: &str =
+    "enter: apply / rank  tab: mode  F2: guesser  ^Z/^Y: undo/redo  ^N: new game  esc: quit";
+
+/// What the event loop does after a key.
+#[derive(Debug, PartialEq, Eq)]
+enum KeyAction {
+    None,
+    Rank,
+    Quit,
+}
+
+/// Full-screen front-end over the same game state as the REPL.
+struct Tui<'a, const N: usize> {
+    game: &'a Game<N>,
+    session: SolverSession<'a, N>,
+    guesser: &'static str,
+    ranking: Vec<(Word<N>, f32)>,
+    ranking_title: String,
+    input: String,
+    message: Line<'static>,
+    scroll: usize,
+    keyboard: Keyboard,
+}
+
+impl<'a, const N: usize> Tui<'a, N> {
+    fn new(game: &'a Game<N>) -> Self {
+        Self {
+            game,
+            session: SolverSession::new(&game.dictionary, game.lies).with_prepared_patterns(),
+            guesser: game.guesser,
+            ranking: vec![],
+            ranking_title: "ranking".to_string(),
+            input: String::new(),
+            message: Line::from(KEYS_HELP).dim(),
+            scroll: 0,
+            keyboard: Keyboard::for_words(&game.dictionary.valid).with_lies(game.lies),
+        }
+    }
+
+    fn resume(&mut self, saved: &SavedGame) {
+        match self.game.restore(saved, &mut self.session) {
+            Ok(()) => self.update(),
+            Err(e) => self.error(format!("couldn't resume the game: {e}")),
+        }
+    }
+
+    /// Recomputes what depends on the history.
+    fn update(&mut self) {
+        self.keyboard
+            .set_patterns(&self.session.history().patterns());
+        self.ranking = vec![];
+        self.ranking_title = "ranking".to_string();
+        self.scroll = 0;
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        self.message = Line::from(message.into()).red();
+    }
+
+    fn info(&mut self, message: impl Into<String>) {
+        self.message = Line::from(message.into()).yellow();
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame, &self.ranking, &self.ranking_title))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            match self.handle_key(key) {
+                KeyAction::None => {}
+                KeyAction::Rank => self.rank(terminal)?,
+                KeyAction::Quit => return Ok(()),
+            }
+        }
+    }
+
+    /// Updates the state for `key`, ranking is left to the caller as it needs the terminal.
+    fn handle_key(&mut self, key: KeyEvent) -> KeyAction {
+        if key.kind != KeyEventKind::Press {
+            return KeyAction::None;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return KeyAction::Quit,
+            KeyCode::Esc if self.input.is_empty() => return KeyAction::Quit,
+            KeyCode::Esc => self.input.clear(),
+            KeyCode::Char('z') if ctrl => match self.session.undo() {
+                Some(step) => {
+                    let message = format!("undone {}", step_text(step));
+                    self.update();
+                    self.info(message);
+                }
+                None => self.error("nothing to undo"),
+            },
+            KeyCode::Char('y') if ctrl => match self.session.redo() {
+                Some(step) => {
+                    let message = format!("redone {}", step_text(step));
+                    self.update();
+                    self.info(message);
+                }
+                None => self.error("nothing to redo"),
+            },
+            KeyCode::Char('n') if ctrl => {
+                self.session.reset();
+                self.update();
+                self.info("new game");
+            }
+            KeyCode::Char(c) if !ctrl => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Tab => {
+                self.session.set_mode(match self.session.mode() {
+                    Mode::Hard => Mode::Normal,
+                    Mode::Normal => Mode::Hard,
+                });
+                self.ranking = vec![];
+            }
+            KeyCode::F(2) => {
+                let names = self.game.guessers.names().collect_vec();
+                let current = names.iter().position(|&name| name == self.guesser);
+                self.guesser = names[current.map_or(0, |i| (i + 1) % names.len())];
+                self.ranking = vec![];
+                self.info(format!("ranking with {}", self.guesser));
+            }
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll += 10,
+            KeyCode::Enter if self.input.trim().is_empty() => return KeyAction::Rank,
+            KeyCode::Enter => self.apply_input(),
+            _ => {}
+        }
+        KeyAction::None
+    }
+
+    fn apply_input(&mut self) {
+        match parse_entries(&self.input, &self.session.valid()) {
+            Ok(patterns) => {
+                for pattern in patterns {
+                    if let Err(e) = self.session.apply(pattern) {
+                        self.error(e.to_string());
+                        return;
+                    }
+                }
+                self.input.clear();
+                self.update();
+                self.message = match self.session.candidates() {
+                    [answer] => Line::from(format!("answer: {answer}")).green().bold(),
+                    _ => Line::from(KEYS_HELP).dim(),
+                };
+            }
+            Err(EntryError { column, message }) => {
+                self.error(format!("column {}: {message}", column + 1));
+            }
+        }
+    }
+
+    /// Ranks the guesses redrawing the ranking panel as it goes, Esc stops the ranking.
+    fn rank(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        if self.session.candidates().is_empty() {
+            self.error("no possible words left");
+            return Ok(());
+        }
+
+        let book_guess = match self.session.mode() {
+            Mode::Hard => None,
+            Mode::Normal if self.guesser == self.game.guesser => {
+                self.game.opening_book_guess(self.session.history())
+            }
+            Mode::Normal => None,
+        };
+        if let Some(guess) = book_guess {
+            self.ranking = vec![guess];
+            self.ranking_title = "from the opening book".to_string();
+            return Ok(());
+        }
+
+        let total = self.session.valid_guesses().len();
+        let guesser = self.game.guessers.get(self.guesser).unwrap().guesser();
+        let token = CancellationToken::new();
+        let budget = self.game.ranking_budget(&token);
+        let mut drawn = Ok(());
+        let ranking = self.session.rank(guesser, &budget, &mut |guesses| {
+            let title = format!("ranked {} of {total}", guesses.len());
+            if drawn.is_ok() {
+                drawn = terminal
+                    .draw(|frame| self.draw(frame, guesses, &title))
+                    .map(|_| ());
+            }
+            if stop_requested() {
+                token.cancel();
+            }
+        });
+        drawn?;
+        let ranking = match ranking {
+            Ok(ranking) => ranking,
+            Err(e) => {
+                self.error(e.to_string());
+                return Ok(());
+            }
+        };
+
+        self.ranking_title = match ranking.complete {
+            true => format!("ranking by {}", self.guesser),
+            false => format!("ranked {} of {} (stopped)", ranking.guesses.len(), total),
+        };
+        self.ranking = ranking.guesses;
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame, ranking: &[(Word<N>, f32)], ranking_title: &str) {
+        let [status, main, input, message] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, candidates, ranking_area] = Layout::horizontal([
+            Constraint::Min(4 * N as u16 + 12),
+            Constraint::Length(N as u16 + 8),
+            Constraint::Length(N as u16 + 16),
+        ])
+        .areas(main);
+        let [board, keyboard] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(self.keyboard.rows().len() as u16 + 2),
+        ])
+        .areas(left);
+
+        let mode = match self.session.mode() {
+            Mode::Hard => Span::from("hard").red(),
+            Mode::Normal => Span::from("norm").green(),
+        };
+        let mut status_line = vec![
+            Span::from("["),
+            mode,
+            Span::from(format!("] {} | ", self.guesser)),
+            Span::from(format!("{} words left", self.session.candidates().len())),
+        ];
+        if self.game.lies > 0 {
+            status_line.push(Span::from(format!(" | lies: {}", self.game.lies)));
+        }
+        frame.render_widget(Line::from(status_line), status);
+
+        self.draw_board(frame, board);
+        self.draw_keyboard(frame, keyboard);
+        self.draw_candidates(frame, candidates);
+        self.draw_ranking(frame, ranking_area, ranking, ranking_title);
+
+        frame.render_widget(
+            Paragraph::new(self.input.as_str()).block(Block::bordered().title("guess colors")),
+            input,
+        );
+        frame.render_widget(&self.message, message);
+    }
+
+    fn draw_board(&self, frame: &mut Frame, area: Rect) {
+        let lines = self
+            .session
+            .history()
+            .steps()
+            .iter()
+            .map(|step| match step {
+                Step::Guess(pattern) => Line::from(
+                    pattern
+                        .iter()
+                        .map(|pattern_letter| {
+                            let letter = format!(" {} ", pattern_letter.letter().to_uppercase());
+                            Span::styled(letter, letter_style(Some(pattern_letter.letter_type())))
+                        })
+                        .collect_vec(),
+                ),
+                step => Line::from(step_text(step)).dim(),
+            })
+            .collect_vec();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("board")),
+            area,
+        );
+    }
+
+    fn draw_keyboard(&self, frame: &mut Frame, area: Rect) {
+        let lines = self
+            .keyboard
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut spans = vec![Span::from(" ".repeat(i))];
+                spans.extend(row.iter().map(|&letter| {
+                    Span::styled(
+                        format!(" {} ", letter.to_uppercase()),
+                        letter_style(self.keyboard.letter(letter).map(LetterState::color)),
+                    )
+                }));
+                Line::from(spans)
+            })
+            .collect_vec();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("keyboard")),
+            area,
+        );
+    }
+
+    fn draw_candidates(&self, frame: &mut Frame, area: Rect) {
+        let candidates = self.session.candidates();
+        let scroll = self.scroll.min(candidates.len().saturating_sub(1));
+        let items = candidates[scroll..]
+            .iter()
+            .map(|word| ListItem::new(word.to_string()))
+            .collect_vec();
+
+        let title = format!("{} left", candidates.len());
+        frame.render_widget(List::new(items).block(Block::bordered().title(title)), area);
+    }
+
+    fn draw_ranking(&self, frame: &mut Frame, area: Rect, ranking: &[(Word<N>, f32)], title: &str) {
+        let items = ranking
+            .iter()
+            .take(area.height as usize)
+            .map(|(word, rank)| {
+                let line = format!("{word}: {rank:.2}");
+                match self.session.candidates().contains(word) {
+                    true => ListItem::new(line).green(),
+                    false => ListItem::new(line),
+                }
+            })
+            .collect_vec();
+
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(title.to_string())),
+            area,
+        );
+    }
+}
+
+fn step_text<const N: usize>(step: &Step<N>) -> String {
+    match step {
+        Step::Guess(pattern) => pattern.word().to_string(),
+        step => step.to_string(),
+    }
+}
+
+fn letter_style(letter_type: Option<LetterType>) -> Style {
+    let style = Style::new().fg(Color::Black).add_modifier(Modifier::BOLD);
+    match letter_type {
+        Some(LetterType::Green) => style.bg(Color::Green),
+        Some(LetterType::Yellow) => style.bg(Color::Yellow),
+        Some(LetterType::Gray) => style.bg(Color::DarkGray),
+        None => style.bg(Color::Gray),
+    }
+}
+
+/// Whether Esc or Ctrl-C is waiting in the input, other keys are dropped.
+fn stop_requested() -> bool {
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        if let Ok(Event::Key(KeyEvent {
+            code, modifiers, ..
+        })) = event::read()
+        {
+            let ctrl_c = code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL);
+            if code == KeyCode::Esc || ctrl_c {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+impl<const N: usize> Game<N> {
+    /// Runs the games in a full-screen terminal UI instead of the REPL.
+    pub fn run_tui(&self) -> io::Result<()> {
+        let mut tui = Tui::new(self);
+        if let Some(saved) = &self.resumed {
+            tui.resume(saved);
+        }
+
+        let mut terminal = ratatui::init();
+        let result = tui.run(&mut terminal);
+        ratatui::restore();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+    use crate::{
+        guesser::GuesserRegistry,
+        test_util::{dictionary, words},
+    };
+
+    fn game() -> Game<5> {
+        Game::new(dictionary(), GuesserRegistry::default(), "naive").unwrap()
+    }
+
+    fn press(tui: &mut Tui<5>, code: KeyCode) -> KeyAction {
+        tui.handle_key(KeyEvent::from(code))
+    }
+
+    fn ctrl(tui: &mut Tui<5>, c: char) -> KeyAction {
+        tui.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    fn type_text(tui: &mut Tui<5>, text: &str) {
+        for c in text.chars() {
+            assert_eq!(press(tui, KeyCode::Char(c)), KeyAction::None);
+        }
+    }
+
+    fn screen(tui: &Tui<5>) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal
+            .draw(|frame| tui.draw(frame, &tui.ranking, &tui.ranking_title))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|line| line.iter().map(|cell| cell.symbol()).join(""))
+            .join("\n")
+    }
+
+    fn color(tui: &Tui<5>, letter: char) -> Option<LetterType> {
+        tui.keyboard.letter(letter).map(LetterState::color)
+    }
+
+    #[test]
+    fn test_enter_guess() {
+        let game = game();
+        let mut tui = Tui::new(&game);
+
+        type_text(&mut tui, "crate yggyx");
+        assert_eq!(press(&mut tui, KeyCode::Enter), KeyAction::None);
+        assert_eq!(tui.input, "crate yggyx");
+        assert_eq!(
+            tui.message.to_string(),
+            "column 11: expecting a color: g, y or ."
+        );
+        assert!(tui.session.history().steps().is_empty());
+
+        assert_eq!(press(&mut tui, KeyCode::Backspace), KeyAction::None);
+        type_text(&mut tui, "g");
+        assert_eq!(press(&mut tui, KeyCode::Enter), KeyAction::None);
+        assert!(tui.input.is_empty());
+        assert_eq!(tui.session.candidates(), words(&["trace"]));
+        assert_eq!(tui.message.to_string(), "answer: trace");
+        assert_eq!(color(&tui, 'c'), Some(LetterType::Yellow));
+        assert_eq!(color(&tui, 'r'), Some(LetterType::Green));
+        assert_eq!(color(&tui, 'b'), None);
+
+        let screen = screen(&tui);
+        assert!(screen.contains("1 words left"));
+        assert!(screen.contains(" C  R  A  T  E "));
+    }
+
+    #[test]
+    fn test_mode_and_guesser_keys() {
+        let game = game();
+        let mut tui = Tui::new(&game);
+        assert!(screen(&tui).starts_with("[norm] naive | 4 words left"));
+
+        assert_eq!(press(&mut tui, KeyCode::Tab), KeyAction::None);
+        assert_eq!(tui.session.mode(), Mode::Hard);
+        assert_eq!(press(&mut tui, KeyCode::F(2)), KeyAction::None);
+        assert_eq!(tui.guesser, "bfs");
+        assert!(screen(&tui).starts_with("[hard] bfs | 4 words left"));
+
+        assert_eq!(press(&mut tui, KeyCode::Tab), KeyAction::None);
+        assert_eq!(tui.session.mode(), Mode::Normal);
+        press(&mut tui, KeyCode::F(2));
+        assert_eq!(tui.guesser, "naive");
+
+        assert_eq!(press(&mut tui, KeyCode::Enter), KeyAction::Rank);
+        type_text(&mut tui, "cr");
+        assert_eq!(press(&mut tui, KeyCode::Esc), KeyAction::None);
+        assert!(tui.input.is_empty());
+        assert_eq!(press(&mut tui, KeyCode::Esc), KeyAction::Quit);
+        assert_eq!(ctrl(&mut tui, 'c'), KeyAction::Quit);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let game = game();
+        let mut tui = Tui::new(&game);
+
+        assert_eq!(ctrl(&mut tui, 'z'), KeyAction::None);
+        assert_eq!(tui.message.to_string(), "nothing to undo");
+
+        type_text(&mut tui, "below .y...; crate yggyg");
+        press(&mut tui, KeyCode::Enter);
+        assert_eq!(tui.session.history().steps().len(), 2);
+        assert_eq!(color(&tui, 'c'), Some(LetterType::Yellow));
+
+        ctrl(&mut tui, 'z');
+        assert_eq!(tui.message.to_string(), "undone crate");
+        assert_eq!(tui.session.candidates(), words(&["crate", "trace"]));
+        assert_eq!(color(&tui, 'c'), None);
+        assert_eq!(color(&tui, 'e'), Some(LetterType::Yellow));
+
+        ctrl(&mut tui, 'y');
+        assert_eq!(tui.message.to_string(), "redone crate");
+        assert_eq!(tui.session.candidates(), words(&["trace"]));
+        assert_eq!(color(&tui, 'c'), Some(LetterType::Yellow));
+        assert_eq!(ctrl(&mut tui, 'y'), KeyAction::None);
+        assert_eq!(tui.message.to_string(), "nothing to redo");
+
+        ctrl(&mut tui, 'n');
+        assert!(tui.session.history().steps().is_empty());
+        assert_eq!(tui.session.candidates().len(), 4);
+        assert_eq!(color(&tui, 'e'), None);
+    }
+}
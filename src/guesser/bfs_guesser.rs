@@ -5,7 +5,7 @@ use priority_queue::PriorityQueue;
 
 use crate::words::{Pattern, PatternCache, Word};
 
-use super::Guesser;
+use super::{Guesser, GuesserError, GuesserOption, RankBudget};
 
 #[derive(PartialEq, Eq)]
 pub struct P {
@@ -34,29 +34,51 @@ impl Ord for P {
     }
 }
 
-pub struct BfsGuesser;
+pub struct BfsGuesser {
+    len_to_find: usize,
+    max_depth: usize,
+}
+
+impl Default for BfsGuesser {
+    fn default() -> Self {
+        Self::new(1, 3)
+    }
+}
 
 impl BfsGuesser {
-    const LEN_TO_FIND: usize = 1;
-    const MAX_DEPTH: usize = 3;
+    pub fn new(len_to_find: usize, max_depth: usize) -> Self {
+        Self {
+            len_to_find,
+            max_depth,
+        }
+    }
+
+    /// Whether `word` is consistent with any of the patterns that may be `reported`.
+    fn may_remain<const N: usize>(word: &Word<N>, reported: &[Pattern<N>], lies: usize) -> bool {
+        reported
+            .iter()
+            .any(|pattern| pattern.is_consistent(word, lies))
+    }
 
     #[allow(dead_code)]
     fn rank_guess_against_answer<const N: usize>(
+        &self,
         first_guess: &Word<N>,
         answer: &Word<N>,
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         _pattern_cache: &PatternCache<N>,
+        lies: usize,
     ) -> usize {
         let valid_guesses = BTreeSet::from_iter(valid_guesses.iter());
 
-        let first_pattern = Pattern::from_guess(first_guess, answer);
+        let first_reported = Pattern::from_guess(first_guess, answer).lie_variants(lies);
         let first_answeres_left = possible_answers
             .iter()
-            .filter(|answer| answer.matches(&first_pattern))
+            .filter(|answer| Self::may_remain(answer, &first_reported, lies))
             .count();
 
-        if first_answeres_left <= Self::LEN_TO_FIND {
+        if first_answeres_left <= self.len_to_find {
             return 1;
         }
 
@@ -72,7 +94,7 @@ impl BfsGuesser {
             }
 
             // too deep, we can do better
-            if already_guessed.len() >= Self::MAX_DEPTH {
+            if already_guessed.len() >= self.max_depth {
                 return already_guessed.len();
             }
 
@@ -89,17 +111,20 @@ impl BfsGuesser {
 
                 let depth = guesses.len();
 
+                let reported: Vec<_> = guesses
+                    .iter()
+                    .map(|&guess| Pattern::from_guess(guess, answer).lie_variants(lies))
+                    .collect();
                 let answers_left = possible_answers
                     .iter()
                     .filter(|&possible_answer| {
-                        guesses
+                        reported
                             .iter()
-                            .map(|&guess| Pattern::from_guess(guess, answer))
-                            .all(|pattern| possible_answer.matches(&pattern))
+                            .all(|reported| Self::may_remain(possible_answer, reported, lies))
                     })
                     .count();
 
-                if answers_left <= Self::LEN_TO_FIND {
+                if answers_left <= self.len_to_find {
                     return depth;
                 }
 
@@ -110,23 +135,27 @@ impl BfsGuesser {
         panic!("out of guesses!");
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn rank_guess_against_answer_deque<const N: usize>(
+        &self,
         first_guess: &Word<N>,
         answer: &Word<N>,
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         _pattern_cache: &PatternCache<N>,
-    ) -> usize {
+        lies: usize,
+        budget: &RankBudget,
+    ) -> Option<usize> {
         let valid_guesses = BTreeSet::from_iter(valid_guesses.iter());
 
-        let first_pattern = Pattern::from_guess(first_guess, answer);
+        let first_reported = Pattern::from_guess(first_guess, answer).lie_variants(lies);
         let first_answeres_left = possible_answers
             .iter()
-            .filter(|answer| answer.matches(&first_pattern))
+            .filter(|answer| Self::may_remain(answer, &first_reported, lies))
             .count();
 
-        if first_answeres_left <= Self::LEN_TO_FIND {
-            return 1;
+        if first_answeres_left <= self.len_to_find {
+            return Some(1);
         }
 
         let mut deque = VecDeque::new();
@@ -136,8 +165,8 @@ impl BfsGuesser {
 
         while let Some(already_guessed) = deque.pop_front() {
             // too deep, we can do better
-            if already_guessed.len() >= Self::MAX_DEPTH {
-                return already_guessed.len();
+            if already_guessed.len() >= self.max_depth {
+                return Some(already_guessed.len());
             }
 
             let new_guesses = valid_guesses.difference(&already_guessed).map(|&guess| {
@@ -147,19 +176,26 @@ impl BfsGuesser {
             });
 
             for guesses in new_guesses {
+                if budget.is_exhausted() {
+                    return None;
+                }
+
                 if deque.contains(&guesses) {
                     continue;
                 }
 
                 let depth = guesses.len();
 
+                let reported: Vec<_> = guesses
+                    .iter()
+                    .map(|&guess| Pattern::from_guess(guess, answer).lie_variants(lies))
+                    .collect();
                 let answers_left = possible_answers
                     .iter()
                     .filter(|&possible_answer| {
-                        guesses
+                        reported
                             .iter()
-                            .map(|&guess| Pattern::from_guess(guess, answer))
-                            .all(|pattern| possible_answer.matches(&pattern))
+                            .all(|reported| Self::may_remain(possible_answer, reported, lies))
                         // cache
                         // .entry((guess, answer))
                         // .or_insert_with(|| Pattern::from_guess(guess, answer))
@@ -167,8 +203,8 @@ impl BfsGuesser {
                     })
                     .count();
 
-                if answers_left <= Self::LEN_TO_FIND {
-                    return depth;
+                if answers_left <= self.len_to_find {
+                    return Some(depth);
                 }
 
                 deque.push_back(guesses);
@@ -185,27 +221,82 @@ impl<const N: usize> Guesser<N> for BfsGuesser {
         guess: &Word<N>,
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
-        _pattern_cache: &PatternCache<N>,
+        pattern_cache: &PatternCache<N>,
+        lies: usize,
     ) -> f32 {
+        self.rank_guess_within(
+            guess,
+            valid_guesses,
+            possible_answers,
+            pattern_cache,
+            lies,
+            &RankBudget::unlimited(),
+        )
+        .unwrap()
+    }
+
+    /// Averages the search depth over `possible_answers`, giving up once `budget` is exhausted.
+    fn rank_guess_within(
+        &self,
+        guess: &Word<N>,
+        valid_guesses: &[Word<N>],
+        possible_answers: &[Word<N>],
+        pattern_cache: &PatternCache<N>,
+        lies: usize,
+        budget: &RankBudget,
+    ) -> Option<f32> {
         let total: usize = possible_answers
             .iter()
             .map(|answer| {
-                // Self::rank_guess_against_answer(
+                // self.rank_guess_against_answer(
                 //     guess,
                 //     answer,
                 //     valid_guesses,
                 //     possible_answers,
                 //     pattern_cache,
+                //     lies,
                 // )
-                Self::rank_guess_against_answer_deque(
+                self.rank_guess_against_answer_deque(
                     guess,
                     answer,
                     valid_guesses,
                     possible_answers,
-                    _pattern_cache,
+                    pattern_cache,
+                    lies,
+                    budget,
                 )
             })
-            .sum();
-        (total as f32) / (possible_answers.len() as f32)
+            .sum::<Option<usize>>()?;
+        Some((total as f32) / (possible_answers.len() as f32))
+    }
+
+    fn options(&self) -> Vec<GuesserOption> {
+        vec![
+            GuesserOption::new(
+                "len_to_find",
+                "stop searching once this many answers are left",
+                self.len_to_find,
+            ),
+            GuesserOption::new(
+                "max_depth",
+                "give up searching after this many guesses",
+                self.max_depth,
+            ),
+        ]
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), GuesserError> {
+        let option = match name {
+            "len_to_find" => &mut self.len_to_find,
+            "max_depth" => &mut self.max_depth,
+            _ => return Err(GuesserError::UnknownOption(name.to_string())),
+        };
+
+        *option = value.parse().map_err(|_| GuesserError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        })?;
+
+        Ok(())
     }
 }
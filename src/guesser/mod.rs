@@ -1,5 +1,16 @@
-use crate::words::{PatternCache, Word};
-use rayon::prelude::*;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    parallel::*,
+    words::{PatternCache, Word},
+};
 
 pub mod naive_guesser;
 pub use naive_guesser::NaiveGuesser;
@@ -7,6 +18,11 @@ pub use naive_guesser::NaiveGuesser;
 pub mod bfs_guesser;
 pub use bfs_guesser::BfsGuesser;
 
+pub mod registry;
+pub use registry::{GuesserEntry, GuesserRegistry};
+
+/// Ranks guesses, lower is better. `lies` is the number of letters in every pattern colored
+/// wrong, as in Fibble, 0 for regular feedback.
 pub trait Guesser<const N: usize>: Send + Sync {
     fn rank_guess(
         &self,
@@ -14,6 +30,7 @@ pub trait Guesser<const N: usize>: Send + Sync {
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         pattern_cache: &PatternCache<N>,
+        lies: usize,
     ) -> f32;
 
     fn rank_guesses(
@@ -21,47 +38,281 @@ pub trait Guesser<const N: usize>: Send + Sync {
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         pattern_cache: &PatternCache<N>,
+        lies: usize,
     ) -> Vec<(Word<N>, f32)> {
         let mut sorted_guesses: Vec<_> = valid_guesses
             .into_par_iter()
             .map(|guess| {
                 (
                     *guess,
-                    self.rank_guess(guess, valid_guesses, possible_answers, pattern_cache),
+                    self.rank_guess(guess, valid_guesses, possible_answers, pattern_cache, lies),
                 )
             })
             .collect();
 
-        sorted_guesses
-            .as_parallel_slice_mut()
-            .sort_unstable_by(|(_w1, n1), (_w2, n2)| n1.partial_cmp(n2).unwrap());
+        sort_ranked(&mut sorted_guesses);
 
         sorted_guesses
     }
-}
 
-pub enum GuesserWrapper {
-    Naive(NaiveGuesser),
-    Bfs(BfsGuesser),
-}
+    /// Like [`Guesser::rank_guesses`], but stops once `budget` is exhausted and returns the
+    /// guesses ranked so far. `on_progress` is called with the sorted partial ranking after
+    /// every batch of guesses.
+    fn rank_guesses_within(
+        &self,
+        valid_guesses: &[Word<N>],
+        possible_answers: &[Word<N>],
+        pattern_cache: &PatternCache<N>,
+        lies: usize,
+        budget: &RankBudget,
+        on_progress: &mut RankProgress<N>,
+    ) -> Ranking<N> {
+        rank_within(valid_guesses, budget, on_progress, |guess| {
+            self.rank_guess_within(
+                guess,
+                valid_guesses,
+                possible_answers,
+                pattern_cache,
+                lies,
+                budget,
+            )
+        })
+    }
 
-impl GuesserWrapper {
-    pub fn rank_guesses<const N: usize>(
+    /// Like [`Guesser::rank_guess`], but may give up with `None` once `budget` is exhausted.
+    fn rank_guess_within(
         &self,
+        guess: &Word<N>,
         valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         pattern_cache: &PatternCache<N>,
-    ) -> Vec<(Word<N>, f32)> {
-        match self {
-            GuesserWrapper::Naive(g) => {
-                g.rank_guesses(valid_guesses, possible_answers, pattern_cache)
+        lies: usize,
+        _budget: &RankBudget,
+    ) -> Option<f32> {
+        Some(self.rank_guess(guess, valid_guesses, possible_answers, pattern_cache, lies))
+    }
+
+    /// Tunable parameters of the guesser along with their current values.
+    fn options(&self) -> Vec<GuesserOption> {
+        vec![]
+    }
+
+    fn set_option(&mut self, name: &str, _value: &str) -> Result<(), GuesserError> {
+        Err(GuesserError::UnknownOption(name.to_string()))
+    }
+}
+
+/// Callback receiving the sorted partial ranking.
+pub type RankProgress<'a, const N: usize> = dyn FnMut(&[(Word<N>, f32)]) + 'a;
+
+const RANK_BATCH_PER_THREAD: usize = 4;
+
+/// Ranks `valid_guesses` in parallel batches until `budget` is exhausted. `rank` may give up on a
+/// guess by returning `None`, such guesses are left out of the ranking.
+pub fn rank_within<const N: usize, F>(
+    valid_guesses: &[Word<N>],
+    budget: &RankBudget,
+    on_progress: &mut RankProgress<N>,
+    rank: F,
+) -> Ranking<N>
+where
+    F: Fn(&Word<N>) -> Option<f32> + Sync,
+{
+    let batch_size = current_num_threads() * RANK_BATCH_PER_THREAD;
+    let mut sorted_guesses = Vec::with_capacity(valid_guesses.len());
+
+    for batch in valid_guesses.chunks(batch_size) {
+        if budget.is_exhausted() {
+            break;
+        }
+
+        sorted_guesses.par_extend(batch.into_par_iter().filter_map(|guess| {
+            if budget.is_exhausted() {
+                return None;
             }
-            GuesserWrapper::Bfs(g) => {
-                g.rank_guesses(valid_guesses, possible_answers, pattern_cache)
+            rank(guess).map(|rank| (*guess, rank))
+        }));
+        sort_ranked(&mut sorted_guesses);
+
+        on_progress(&sorted_guesses);
+    }
+
+    Ranking {
+        complete: sorted_guesses.len() == valid_guesses.len(),
+        guesses: sorted_guesses,
+    }
+}
+
+fn sort_ranked<const N: usize>(guesses: &mut [(Word<N>, f32)]) {
+    guesses
+        .as_parallel_slice_mut()
+        .sort_unstable_by(|(_w1, n1), (_w2, n2)| n1.total_cmp(n2));
+}
+
+/// Result of [`Guesser::rank_guesses_within`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ranking<const N: usize> {
+    pub guesses: Vec<(Word<N>, f32)>,
+    /// `false` if the budget ran out before every guess was ranked.
+    pub complete: bool,
+}
+
+/// Flag shared between threads to stop a running ranking.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on how long [`Guesser::rank_guesses_within`] may run.
+#[derive(Clone, Debug, Default)]
+pub struct RankBudget {
+    pub deadline: Option<Instant>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl RankBudget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Stops after `timeout`, a timeout too long to have a deadline is no limit.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Instant::now().checked_add(timeout);
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+            || self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// Values of the options of `guesser` by name, for saving them along with what it ranked.
+pub fn option_values<const N: usize>(guesser: &dyn Guesser<N>) -> BTreeMap<String, String> {
+    guesser
+        .options()
+        .into_iter()
+        .map(|option| (option.name.to_string(), option.value))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuesserOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: String,
+}
+
+impl GuesserOption {
+    pub fn new(name: &'static str, description: &'static str, value: impl ToString) -> Self {
+        Self {
+            name,
+            description,
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GuesserError {
+    UnknownGuesser(String),
+    UnknownOption(String),
+    InvalidValue { name: String, value: String },
+}
+
+impl std::error::Error for GuesserError {}
+
+impl std::fmt::Display for GuesserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuesserError::UnknownGuesser(name) => write!(f, "unknown guesser `{name}`"),
+            GuesserError::UnknownOption(name) => write!(f, "unknown option `{name}`"),
+            GuesserError::InvalidValue { name, value } => {
+                write!(f, "invalid value `{value}` for option `{name}`")
             }
         }
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_rank_guesses_within_unlimited() {
+        let valid = words(&["crate", "slate", "trace", "light", "value", "below"]);
+        let answers = words(&["crate", "trace", "below"]);
+        let cache = PatternCache::new();
+
+        let mut progress_calls = 0;
+        let ranking = NaiveGuesser.rank_guesses_within(
+            &valid,
+            &answers,
+            &cache,
+            0,
+            &RankBudget::unlimited(),
+            &mut |_| progress_calls += 1,
+        );
+
+        assert!(ranking.complete);
+        assert!(progress_calls > 0);
+        assert_eq!(
+            ranking.guesses,
+            NaiveGuesser.rank_guesses(&valid, &answers, &cache, 0)
+        );
+    }
+
+    #[test]
+    fn test_rank_guesses_without_answers() {
+        let valid = words(&["crate", "slate", "trace"]);
+
+        // every rank is NaN
+        let ranked = NaiveGuesser.rank_guesses(&valid, &[], &PatternCache::new(), 0);
+        assert_eq!(ranked.len(), valid.len());
+    }
+
+    #[test]
+    fn test_rank_guesses_within_cancelled() {
+        let valid = words(&["crate", "slate", "trace"]);
+        let cache = PatternCache::new();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let budget = RankBudget::unlimited().with_cancellation(token);
+
+        let ranking =
+            NaiveGuesser.rank_guesses_within(&valid, &valid, &cache, 0, &budget, &mut |_| {
+                panic!("nothing should be ranked")
+            });
+        assert!(!ranking.complete);
+        assert!(ranking.guesses.is_empty());
+
+        let budget = RankBudget::unlimited().with_timeout(Duration::ZERO);
+        assert!(budget.is_exhausted());
+
+        let budget = RankBudget::unlimited().with_timeout(Duration::from_secs_f64(1e19));
+        assert!(!budget.is_exhausted());
+    }
+}
@@ -1,10 +1,45 @@
+use itertools::Itertools;
+
 use crate::words::{Pattern, PatternCache, Word};
 
 use super::Guesser;
 
 pub struct NaiveGuesser;
 
-impl NaiveGuesser {}
+impl NaiveGuesser {
+    /// With lies any of the lie variants of the true pattern may be reported, so the number of
+    /// answers left is averaged over them. Answers are grouped by their true pattern, as there
+    /// are far fewer patterns than answers.
+    fn rank_guess_with_lies<const N: usize>(
+        guess: &Word<N>,
+        possible_answers: &[Word<N>],
+        lies: usize,
+    ) -> f32 {
+        let pattern_counts = possible_answers
+            .iter()
+            .counts_by(|answer| Pattern::from_guess(guess, answer));
+
+        let matches: f32 = pattern_counts
+            .iter()
+            .map(|(pattern, &count)| {
+                let reported = pattern.lie_variants(lies);
+                let matches: usize = reported
+                    .iter()
+                    .map(|reported| {
+                        pattern_counts
+                            .iter()
+                            .filter(|(other, _)| other.distance(reported) == lies)
+                            .map(|(_, &count)| count)
+                            .sum::<usize>()
+                    })
+                    .sum();
+                (count * matches) as f32 / (reported.len() as f32)
+            })
+            .sum();
+
+        matches / (possible_answers.len() as f32)
+    }
+}
 
 impl<const N: usize> Guesser<N> for NaiveGuesser {
     fn rank_guess(
@@ -13,7 +48,12 @@ impl<const N: usize> Guesser<N> for NaiveGuesser {
         _valid_guesses: &[Word<N>],
         possible_answers: &[Word<N>],
         _pattern_cache: &PatternCache<N>,
+        lies: usize,
     ) -> f32 {
+        if lies > 0 {
+            return Self::rank_guess_with_lies(guess, possible_answers, lies);
+        }
+
         let matches: usize = possible_answers
             .iter()
             .map(|answer| {
@@ -28,3 +68,33 @@ impl<const N: usize> Guesser<N> for NaiveGuesser {
         (matches as f32) / (possible_answers.len() as f32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_rank_guess_with_lies() {
+        let guess: Word<5> = "slate".parse().unwrap();
+        let answers = words(&["crate", "trace", "below", "elbow", "light", "slate"]);
+        let lies = 1;
+
+        // every answer and every lie, counting the answers consistent with what was reported
+        let expected: f32 = answers
+            .iter()
+            .map(|answer| {
+                let reported = Pattern::from_guess(&guess, answer).lie_variants(lies);
+                let left: usize = reported
+                    .iter()
+                    .map(|pattern| pattern.filter_words_with_lies(&answers, lies).len())
+                    .sum();
+                left as f32 / reported.len() as f32
+            })
+            .sum::<f32>()
+            / answers.len() as f32;
+
+        let rank = NaiveGuesser.rank_guess(&guess, &answers, &answers, &PatternCache::new(), lies);
+        assert!((rank - expected).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,164 @@
+use super::{BfsGuesser, Guesser, GuesserError, NaiveGuesser};
+
+pub struct GuesserEntry<const N: usize> {
+    name: &'static str,
+    description: &'static str,
+    guesser: Box<dyn Guesser<N>>,
+}
+
+impl<const N: usize> GuesserEntry<N> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn guesser(&self) -> &dyn Guesser<N> {
+        self.guesser.as_ref()
+    }
+
+    pub fn guesser_mut(&mut self) -> &mut dyn Guesser<N> {
+        self.guesser.as_mut()
+    }
+}
+
+/// Guessers available by name, in registration order.
+pub struct GuesserRegistry<const N: usize> {
+    entries: Vec<GuesserEntry<N>>,
+}
+
+impl<const N: usize> GuesserRegistry<N> {
+    /// An empty registry, see [`GuesserRegistry::default`] for one with the built-in guessers.
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Adds a guesser, replacing the one already registered under the same name.
+    pub fn register<G: Guesser<N> + 'static>(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        guesser: G,
+    ) {
+        let entry = GuesserEntry {
+            name,
+            description,
+            guesser: Box::new(guesser),
+        };
+
+        match self.entries.iter_mut().find(|e| e.name == name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Result<&GuesserEntry<N>, GuesserError> {
+        self.entries
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| GuesserError::UnknownGuesser(name.to_string()))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut GuesserEntry<N>, GuesserError> {
+        self.entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| GuesserError::UnknownGuesser(name.to_string()))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, GuesserEntry<N>> {
+        self.entries.iter()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|e| e.name)
+    }
+}
+
+impl<const N: usize> Default for GuesserRegistry<N> {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            "naive",
+            "expected number of answers left after the guess",
+            NaiveGuesser,
+        );
+        registry.register(
+            "bfs",
+            "expected number of guesses to narrow down the answer, found by breadth-first search",
+            BfsGuesser::default(),
+        );
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::words::{PatternCache, Word};
+
+    struct ConstGuesser(f32);
+
+    impl<const N: usize> Guesser<N> for ConstGuesser {
+        fn rank_guess(
+            &self,
+            _guess: &Word<N>,
+            _valid_guesses: &[Word<N>],
+            _possible_answers: &[Word<N>],
+            _pattern_cache: &PatternCache<N>,
+            _lies: usize,
+        ) -> f32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = GuesserRegistry::<5>::default();
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["naive", "bfs"]);
+
+        registry.register("const", "always the same", ConstGuesser(1.0));
+        registry.register("naive", "replaced", ConstGuesser(2.0));
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["naive", "bfs", "const"]
+        );
+
+        let words = ["crate".parse().unwrap()];
+        let ranked = registry.get("naive").unwrap().guesser().rank_guesses(
+            &words,
+            &words,
+            &PatternCache::new(),
+            0,
+        );
+        assert_eq!(ranked, vec![(words[0], 2.0)]);
+
+        assert!(matches!(
+            registry.get("nope"),
+            Err(GuesserError::UnknownGuesser(_))
+        ));
+    }
+
+    #[test]
+    fn test_set_option() {
+        let mut registry = GuesserRegistry::<5>::default();
+        let bfs = registry.get_mut("bfs").unwrap().guesser_mut();
+
+        assert_eq!(bfs.set_option("max_depth", "4"), Ok(()));
+        assert_eq!(
+            bfs.options()
+                .iter()
+                .find(|o| o.name == "max_depth")
+                .map(|o| o.value.as_str()),
+            Some("4")
+        );
+        assert!(bfs.set_option("max_depth", "deep").is_err());
+        assert!(bfs.set_option("width", "4").is_err());
+
+        let naive = registry.get_mut("naive").unwrap().guesser_mut();
+        assert!(naive.options().is_empty());
+        assert!(naive.set_option("max_depth", "4").is_err());
+    }
+}
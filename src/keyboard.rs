@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::words::{LetterType, Pattern, Word};
+
+const QWERTY: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const JCUKEN: [&str; 3] = ["йцукенгшщзхъ", "фывапролджэ", "ячсмитьбю"];
+
+/// What the guesses so far tell about a letter.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LetterState {
+    /// Positions where the letter was green.
+    pub green: Vec<usize>,
+    /// Positions where the letter was yellow or gray, so it can't be there.
+    pub forbidden: Vec<usize>,
+    /// The answer has at least this many of the letter.
+    pub min_count: usize,
+    /// The answer has at most this many of the letter, known once it was gray.
+    pub max_count: Option<usize>,
+}
+
+impl LetterState {
+    /// The color of the key, like Wordle shows it: green once the letter was green anywhere,
+    /// yellow if it is known to be in the answer, gray if it isn't.
+    pub fn color(&self) -> LetterType {
+        if !self.green.is_empty() {
+            LetterType::Green
+        } else if self.min_count > 0 {
+            LetterType::Yellow
+        } else {
+            LetterType::Gray
+        }
+    }
+}
+
+/// The keyboard of the game: letters laid out in rows, with the state of every guessed letter.
+pub struct Keyboard {
+    rows: Vec<Vec<char>>,
+    letters: BTreeMap<char, LetterState>,
+    lies: usize,
+}
+
+impl Keyboard {
+    pub fn new(rows: Vec<Vec<char>>) -> Self {
+        Self {
+            rows,
+            letters: BTreeMap::new(),
+            lies: 0,
+        }
+    }
+
+    /// Patterns with `lies` letters colored wrong, any single color may be a lie so they leave
+    /// every letter unknown.
+    pub fn with_lies(mut self, lies: usize) -> Self {
+        self.lies = lies;
+        self
+    }
+
+    /// A keyboard for the alphabet of `words`: QWERTY or ЙЦУКЕН if it has all the letters,
+    /// otherwise the alphabet in rows of 11.
+    pub fn for_words<const N: usize>(words: &[Word<N>]) -> Self {
+        let alphabet = words
+            .iter()
+            .flat_map(|word| word.word)
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let layout = [QWERTY, JCUKEN].into_iter().find(|layout| {
+            alphabet
+                .iter()
+                .all(|&letter| layout.iter().any(|row| row.contains(letter)))
+        });
+        let rows = match layout {
+            Some(layout) => layout.iter().map(|row| row.chars().collect()).collect(),
+            None => alphabet.chunks(11).map(<[char]>::to_vec).collect(),
+        };
+
+        Self::new(rows)
+    }
+
+    /// Sets the letter states to what `patterns` tell, forgetting the patterns applied before.
+    pub fn set_patterns<const N: usize>(&mut self, patterns: &[Pattern<N>]) {
+        self.letters.clear();
+        for pattern in patterns {
+            self.apply(pattern);
+        }
+    }
+
+    /// Adds what `pattern` tells about its letters, nothing if it may lie.
+    pub fn apply<const N: usize>(&mut self, pattern: &Pattern<N>) {
+        if self.lies > 0 {
+            return;
+        }
+
+        let by_letter = pattern
+            .iter()
+            .enumerate()
+            .into_group_map_by(|(_, pattern_letter)| pattern_letter.letter());
+
+        for (letter, positions) in by_letter {
+            let state = self.letters.entry(letter).or_default();
+            let mut shown = 0;
+            let mut gray = false;
+            for (position, pattern_letter) in positions {
+                match pattern_letter.letter_type() {
+                    LetterType::Green => {
+                        shown += 1;
+                        state.green.push(position);
+                    }
+                    LetterType::Yellow => {
+                        shown += 1;
+                        state.forbidden.push(position);
+                    }
+                    LetterType::Gray => {
+                        gray = true;
+                        state.forbidden.push(position);
+                    }
+                }
+            }
+
+            state.green.sort_unstable();
+            state.green.dedup();
+            state.forbidden.sort_unstable();
+            state.forbidden.dedup();
+            state.min_count = state.min_count.max(shown);
+            if gray {
+                state.max_count = Some(state.max_count.map_or(shown, |max| max.min(shown)));
+            }
+        }
+    }
+
+    pub fn rows(&self) -> &[Vec<char>] {
+        &self.rows
+    }
+
+    /// The state of `letter`, `None` if it hasn't been guessed.
+    pub fn letter(&self, letter: char) -> Option<&LetterState> {
+        self.letters.get(&letter)
+    }
+
+    /// The guessed letters with their states, in alphabetical order.
+    pub fn letters(&self) -> impl Iterator<Item = (char, &LetterState)> {
+        self.letters.iter().map(|(&letter, state)| (letter, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_letter_states() {
+        let patterns = [
+            Pattern::<5>::from_description("eerie", "y...g").unwrap(),
+            Pattern::<5>::from_description("slate", "..g.g").unwrap(),
+        ];
+        let mut keyboard = Keyboard::for_words(&words(&["trace"]));
+        keyboard.set_patterns(&patterns);
+
+        let e = keyboard.letter('e').unwrap();
+        assert_eq!(e.color(), LetterType::Green);
+        assert_eq!(e.green, vec![4]);
+        assert_eq!(e.forbidden, vec![0, 1]);
+        assert_eq!((e.min_count, e.max_count), (2, Some(2)));
+
+        let r = keyboard.letter('r').unwrap();
+        assert_eq!(r.color(), LetterType::Gray);
+        assert_eq!((r.min_count, r.max_count), (0, Some(0)));
+
+        let a = keyboard.letter('a').unwrap();
+        assert_eq!((a.min_count, a.max_count), (1, None));
+        assert_eq!(keyboard.letter('q'), None);
+        assert_eq!(keyboard.rows()[0][0], 'q');
+    }
+
+    #[test]
+    fn test_letter_states_with_lies() {
+        let pattern = Pattern::<5>::from_description("slate", "..g.g").unwrap();
+        let mut keyboard = Keyboard::for_words(&words(&["trace"])).with_lies(1);
+        keyboard.apply(&pattern);
+
+        // the green e may be the lie
+        assert_eq!(keyboard.letter('e'), None);
+        assert_eq!(keyboard.letter('s'), None);
+        assert_eq!(keyboard.letters().count(), 0);
+    }
+
+    #[test]
+    fn test_layout() {
+        let keyboard = Keyboard::for_words(&words(&["банан", "мышка"]));
+        assert_eq!(keyboard.rows()[0][0], 'й');
+
+        let keyboard = Keyboard::for_words(&words(&["crème", "brûlé"]));
+        assert_eq!(keyboard.rows().concat().len(), 9);
+    }
+}
@@ -6,22 +6,57 @@
 
 use std::{error::Error, fs::File, io::BufReader, path::Path, str::FromStr};
 
+use chrono::NaiveDate;
+use schedule::Schedule;
 use serde::Deserialize;
 use words::Word;
 
+pub mod absurdle;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "cli")]
 pub mod game;
 pub mod guesser;
+pub mod keyboard;
+#[cfg(feature = "cli")]
+mod line_editor;
+pub mod opening_book;
+mod parallel;
+pub mod partition;
+pub mod past_answers;
+pub mod play;
+#[cfg(feature = "python")]
+mod python;
+pub mod schedule;
+#[cfg(feature = "cli")]
+pub mod server;
+pub mod session;
+pub mod share;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod words;
 
+/// Number of letters in the words of the command line tool and the bindings.
+pub const WORD_LENGTH: usize = 5;
+
+#[derive(Deserialize)]
+struct ScheduleDeserialized {
+    first: NaiveDate,
+    answers: Vec<String>,
+}
+
 #[derive(Deserialize)]
 struct DictionaryDeserialized {
     valid: Vec<String>,
     answers: Vec<String>,
+    schedule: Option<ScheduleDeserialized>,
 }
 
 pub struct Dictionary<const N: usize> {
     pub valid: Vec<Word<N>>,
     pub answers: Vec<Word<N>>,
+    /// Answers of the daily puzzles in order, if the dictionary knows them.
+    pub schedule: Option<Schedule<N>>,
 }
 
 impl<const N: usize> Dictionary<N> {
@@ -32,7 +67,20 @@ impl<const N: usize> Dictionary<N> {
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
-        let DictionaryDeserialized { valid, answers } = Self::read_file(path)?;
+        Self::from_deserialized(Self::read_file(path)?)
+    }
+
+    /// Reads a dictionary in the format of the files from a string.
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_deserialized(serde_json::from_str(json)?)
+    }
+
+    fn from_deserialized(dictionary: DictionaryDeserialized) -> Result<Self, Box<dyn Error>> {
+        let DictionaryDeserialized {
+            valid,
+            answers,
+            schedule,
+        } = dictionary;
         let mut valid: Vec<_> = valid
             .iter()
             .map(AsRef::as_ref)
@@ -44,11 +92,58 @@ impl<const N: usize> Dictionary<N> {
             .map(Word::from_str)
             .collect::<Result<_, _>>()?;
 
+        let schedule = match schedule {
+            Some(ScheduleDeserialized { first, answers }) => Some(Schedule {
+                first,
+                answers: answers
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .map(Word::from_str)
+                    .collect::<Result<_, _>>()?,
+            }),
+            None => None,
+        };
+
         valid.extend(answers.clone());
+        if let Some(schedule) = &schedule {
+            valid.extend(schedule.answers.clone());
+        }
         valid.sort_unstable();
         valid.dedup();
         answers.sort_unstable();
 
-        Ok(Self { valid, answers })
+        Ok(Self {
+            valid,
+            answers,
+            schedule,
+        })
+    }
+
+    /// Removes `excluded` from the answers, they stay valid guesses.
+    pub fn exclude_answers(&mut self, excluded: &[Word<N>]) {
+        self.answers.retain(|answer| !excluded.contains(answer));
+    }
+
+    /// Identifies the word lists, see [`opening_book::fingerprint`].
+    pub fn fingerprint(&self) -> u64 {
+        opening_book::fingerprint(&self.valid, &self.answers)
+    }
+}
+
+#[cfg(test)]
+mod test_util {
+    use super::*;
+
+    pub fn words(words: &[&str]) -> Vec<Word<5>> {
+        words.iter().map(|w| w.parse().unwrap()).collect()
+    }
+
+    /// Answers in anagram pairs, so that some patterns leave two of them.
+    pub fn dictionary() -> Dictionary<5> {
+        Dictionary {
+            valid: words(&["crate", "slate", "trace", "light", "below", "elbow"]),
+            answers: words(&["crate", "trace", "below", "elbow"]),
+            schedule: None,
+        }
     }
 }
@@ -0,0 +1,244 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use colored::*;
+use rustyline::{
+    completion::{Completer, FilenameCompleter},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hint,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+
+use crate::words::{description_colors, Word};
+
+/// What the line being read is going to be, for completion and hints.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Expecting {
+    /// A `:` command or a guessed word.
+    Command,
+    Word,
+    /// Colors of the guessed word, `N` of them.
+    Colors,
+    /// A row of a shared emoji grid.
+    GridRow,
+}
+
+/// Inline hint that is only shown, never inserted into the line.
+struct ShowOnly(String);
+
+impl Hint for ShowOnly {
+    fn display(&self) -> &str {
+        &self.0
+    }
+
+    fn completion(&self) -> Option<&str> {
+        None
+    }
+}
+
+struct InputHelper<const N: usize> {
+    commands: &'static [&'static str],
+    words: Vec<String>,
+    files: FilenameCompleter,
+    expecting: Expecting,
+}
+
+/// What the argument of a command names.
+enum Argument {
+    Word,
+    File,
+    /// `counts` or a file.
+    Share,
+}
+
+fn argument(command: &str) -> Option<Argument> {
+    match command {
+        ":split" | ":ban" | ":allow" | ":why" | ":compare" | ":grid" => Some(Argument::Word),
+        ":save" | ":load" => Some(Argument::File),
+        ":share" => Some(Argument::Share),
+        _ => None,
+    }
+}
+
+impl<const N: usize> InputHelper<N> {
+    /// Start of the word being completed and the commands, dictionary words or other arguments
+    /// it may be, files aside.
+    fn complete_word(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let prefix = &line[..pos];
+        let start = prefix.rfind(' ').map_or(0, |space| space + 1);
+        let word = &prefix[start..];
+
+        let candidates: Vec<&str> = match (self.expecting, prefix.split_once(' ')) {
+            (Expecting::Command, None) if word.starts_with(':') => self.commands.to_vec(),
+            (Expecting::Command | Expecting::Word, None) => {
+                self.words.iter().map(String::as_str).collect()
+            }
+            (Expecting::Command, Some((command, arguments))) => match argument(command) {
+                Some(Argument::Word) => self.words.iter().map(String::as_str).collect(),
+                Some(Argument::Share) if !arguments.contains(' ') => vec!["counts"],
+                _ => vec![],
+            },
+            _ => vec![],
+        };
+
+        let candidates = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(String::from)
+            .collect();
+        (start, candidates)
+    }
+}
+
+impl<const N: usize> Completer for InputHelper<N> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let (start, mut candidates) = self.complete_word(line, pos);
+
+        let command = line[..pos].split_once(' ').map(|(command, _)| command);
+        let file = matches!(
+            command.and_then(argument),
+            Some(Argument::File | Argument::Share)
+        );
+        if self.expecting == Expecting::Command && file {
+            let (start, files) = self.files.complete_path(line, pos)?;
+            candidates.extend(files.into_iter().map(|file| file.replacement));
+            return Ok((start, candidates));
+        }
+        Ok((start, candidates))
+    }
+}
+
+impl<const N: usize> Hinter for InputHelper<N> {
+    type Hint = ShowOnly;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<ShowOnly> {
+        if self.expecting != Expecting::Colors || pos < line.len() {
+            return None;
+        }
+
+        match N.checked_sub(description_colors(line).count()) {
+            Some(0) => None,
+            Some(left) => Some(ShowOnly(format!("{}  {left} more", ".".repeat(left)))),
+            None => Some(ShowOnly(format!("  expecting {N} colors"))),
+        }
+    }
+}
+
+impl<const N: usize> Highlighter for InputHelper<N> {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+}
+
+impl<const N: usize> Validator for InputHelper<N> {}
+
+impl<const N: usize> Helper for InputHelper<N> {}
+
+/// Reads the REPL input with line editing, completion of commands, their arguments and
+/// dictionary words, and history kept between sessions.
+pub struct LineEditor<const N: usize> {
+    editor: Editor<InputHelper<N>, DefaultHistory>,
+    history_file: Option<PathBuf>,
+}
+
+impl<const N: usize> LineEditor<N> {
+    /// Keeps the history in `history_file` if there is one.
+    pub fn new(
+        commands: &'static [&'static str],
+        valid: &[Word<N>],
+        history_file: Option<PathBuf>,
+    ) -> rustyline::Result<Self> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(InputHelper {
+            commands,
+            words: valid.iter().map(Word::to_string).collect(),
+            files: FilenameCompleter::new(),
+            expecting: Expecting::Command,
+        }));
+
+        if let Some(path) = &history_file {
+            // there is no history before the first run
+            let _ = editor.load_history(path);
+        }
+
+        Ok(Self {
+            editor,
+            history_file,
+        })
+    }
+
+    /// The next line, `None` on Ctrl-C or Ctrl-D.
+    pub fn read_line(&mut self, expecting: Expecting) -> Option<String> {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.expecting = expecting;
+        }
+
+        let line = match self.editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => return None,
+            Err(e) => panic!("couldn't read the input: {e}"),
+        };
+
+        if !line.trim().is_empty() {
+            let _ = self.editor.add_history_entry(line.as_str());
+            if let Some(path) = &self.history_file {
+                let _ = self.editor.save_history(path);
+            }
+        }
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    fn helper(expecting: Expecting) -> InputHelper<5> {
+        InputHelper {
+            commands: &[":ban", ":share", ":show", ":save"],
+            words: words(&["crate", "crane", "slate"])
+                .iter()
+                .map(Word::to_string)
+                .collect(),
+            files: FilenameCompleter::new(),
+            expecting,
+        }
+    }
+
+    #[test]
+    fn test_complete_word() {
+        let complete = |expecting, line: &str| helper(expecting).complete_word(line, line.len());
+
+        assert_eq!(
+            complete(Expecting::Command, ":sh"),
+            (0, vec![":share".to_string(), ":show".to_string()])
+        );
+        assert_eq!(
+            complete(Expecting::Command, "cra"),
+            (0, vec!["crate".to_string(), "crane".to_string()])
+        );
+        assert_eq!(
+            complete(Expecting::Command, ":ban crate sl"),
+            (11, vec!["slate".to_string()])
+        );
+        assert_eq!(
+            complete(Expecting::Command, ":share co"),
+            (7, vec!["counts".to_string()])
+        );
+        assert_eq!(complete(Expecting::Command, ":save cr"), (6, vec![]));
+        assert_eq!(complete(Expecting::Command, ":show cr"), (6, vec![]));
+        assert_eq!(complete(Expecting::Word, "crate cr"), (6, vec![]));
+        assert_eq!(complete(Expecting::Colors, "cr"), (0, vec![]));
+    }
+}
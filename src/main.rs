@@ -1,47 +1,517 @@
-use clap::{builder::PossibleValue, Parser, ValueEnum, ValueHint};
+use clap::{
+    builder::{PossibleValue, PossibleValuesParser},
+    error::ErrorKind,
+    CommandFactory, FromArgMatches, Parser, Subcommand, ValueHint,
+};
+use std::{
+    error::Error,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use chrono::{Local, NaiveDate};
+use colored::Colorize;
+use itertools::Itertools;
 use rustybovich::{
-    game::Game,
-    guesser::{BfsGuesser, GuesserWrapper, NaiveGuesser},
-    Dictionary,
+    absurdle::{self, AbsurdleHost},
+    game::{Game, SavedGame},
+    guesser::GuesserRegistry,
+    opening_book::OpeningBook,
+    past_answers::PastAnswers,
+    play::{self, FixedHost, Play},
+    schedule::Puzzle,
+    server::Server,
+    share,
+    words::{Pattern, Word},
+    Dictionary, WORD_LENGTH,
 };
 
-#[derive(Clone, Copy, Debug)]
-enum GuesserType {
-    Naive,
-    Bfs,
+#[derive(Parser, Debug)]
+struct Arguments {
+    #[arg(short = 'g', default_value = "naive")]
+    guesser: String,
+
+    /// Set an option of the guesser, may be repeated
+    #[arg(short = 'o', value_name = "NAME=VALUE")]
+    options: Vec<String>,
+
+    #[arg(
+        short = 'd',
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        required_unless_present = "resume"
+    )]
+    dictionary: Option<PathBuf>,
+
+    /// Resume a game saved with `:save`, with its dictionary, guesser and lies
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    resume: Option<PathBuf>,
+
+    /// Stop ranking guesses after this many seconds and show the best ones found so far
+    #[arg(short = 't', long, value_name = "SECONDS", value_parser = parse_seconds)]
+    time_limit: Option<Duration>,
+
+    /// Take the first two guesses from an opening book built with the `book` command
+    #[arg(short = 'b', long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    book: Option<PathBuf>,
+
+    /// Number of letters colored wrong in every pattern, as in Fibble
+    #[arg(short = 'l', long, default_value_t = 0)]
+    lies: usize,
+
+    /// JSON file with the answers of past daily puzzles by date
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    past_answers: Option<PathBuf>,
+
+    /// Drop the past answers of puzzles before this date from the answers
+    #[arg(long, value_name = "YYYY-MM-DD", requires = "past_answers")]
+    exclude_used_before: Option<NaiveDate>,
+
+    /// Read JSON-RPC requests from stdin a line at a time and write the responses to stdout
+    /// instead of running the REPL
+    #[arg(long)]
+    json_rpc: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-impl ValueEnum for GuesserType {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Naive, Self::Bfs]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rank the first two guesses with the guesser and save them as an opening book
+    Book {
+        #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+
+    /// Guess a word yourself
+    Play {
+        /// Play against Absurdle, a host that changes the answer to dodge the guesses
+        #[arg(long)]
+        absurdle: bool,
+    },
+
+    /// Find the shortest sequence of guesses that wins Absurdle
+    Absurdle {
+        #[arg(long, default_value_t = 8)]
+        max_guesses: usize,
+
+        /// Number of most promising positions kept after every guess
+        #[arg(long, default_value_t = 20)]
+        beam_width: usize,
+    },
+
+    /// Solve in a full-screen terminal UI instead of the REPL
+    Tui,
+
+    /// Look up a daily puzzle in the schedule of the dictionary, then play or solve it
+    Daily {
+        /// Number or date of the puzzle, e.g. `#1234` or `2024-01-31`
+        puzzle: Puzzle,
+
+        /// Show the answer and the guesses of the guesser
+        #[arg(long)]
+        reveal: bool,
+
+        /// Guess the answer yourself
+        #[arg(long, conflicts_with = "solve")]
+        play: bool,
+
+        /// Let the guesser solve the puzzle
+        #[arg(long)]
+        solve: bool,
+    },
+
+    /// Add the answer of a daily puzzle to the past answers file
+    Record {
+        answer: String,
+
+        /// Date of the puzzle, today by default
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        date: Option<NaiveDate>,
+    },
+
+    /// Serve a JSON API over HTTP for solving games from other programs
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+
+    /// Show the answers left and the best guesses after the given guesses
+    Solve {
+        /// Guesses and their colors, e.g. `crate ⬛🟨⬛⬛🟩 lions gg...`
+        #[arg(value_name = "WORD COLORS")]
+        patterns: Vec<String>,
+    },
+}
+
+fn parse_seconds(seconds: &str) -> Result<Duration, Box<dyn Error + Send + Sync>> {
+    Ok(Duration::try_from_secs_f64(seconds.parse()?)?)
+}
+
+fn guessers_help<const N: usize>(guessers: &GuesserRegistry<N>) -> String {
+    let mut help = String::from("Guesser options:");
+    for entry in guessers.iter() {
+        for option in entry.guesser().options() {
+            help += &format!(
+                "\n  {}: {}={} ({})",
+                entry.name(),
+                option.name,
+                option.value,
+                option.description
+            );
+        }
     }
+    help
+}
 
-    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
-        Some(match self {
-            Self::Naive => PossibleValue::new("naive"),
-            Self::Bfs => PossibleValue::new("bfs"),
-        })
+fn build_opening_book<const N: usize>(
+    dictionary: &Dictionary<N>,
+    guessers: &GuesserRegistry<N>,
+    guesser: &str,
+    output: &Path,
+) {
+    let pattern_cache = Pattern::prepare_all(&dictionary.valid, &dictionary.answers);
+    let book = OpeningBook::build(
+        dictionary,
+        guesser,
+        guessers.get(guesser).unwrap().guesser(),
+        &pattern_cache,
+        |done, total| eprint!("\rsecond guesses: {done}/{total}"),
+    )
+    .expect("couldn't make a guess");
+    eprintln!();
+
+    book.to_file(output).unwrap();
+    println!("first guess: {}", book.first.0);
+}
+
+fn solve<const N: usize>(
+    dictionary: Dictionary<N>,
+    guessers: &GuesserRegistry<N>,
+    guesser: &str,
+    lies: usize,
+    patterns: &[String],
+) -> Result<(), Box<dyn Error>> {
+    const SHOW_N: usize = 10;
+
+    if !patterns.len().is_multiple_of(2) {
+        return Err("expected pairs of a word and its colors".into());
+    }
+
+    let mut answers = dictionary.answers;
+    for (word, colors) in patterns.iter().tuples() {
+        let pattern = Pattern::from_description(word, colors)
+            .map_err(|_| format!("`{word} {colors}` isn't a pattern of {N} letters"))?;
+        answers = pattern.filter_words_with_lies(&answers, lies);
+    }
+    println!(
+        "{} answers left: [{}]",
+        answers.len(),
+        answers.iter().take(SHOW_N).join(", ")
+    );
+    if answers.is_empty() {
+        return Ok(());
     }
+
+    let pattern_cache = Pattern::prepare_all(&dictionary.valid, &answers);
+    let ranked = guessers.get(guesser)?.guesser().rank_guesses(
+        &dictionary.valid,
+        &answers,
+        &pattern_cache,
+        lies,
+    );
+    for (guess, rank) in ranked.iter().take(SHOW_N) {
+        println!("{guess} {rank:.3}");
+    }
+    Ok(())
 }
 
-#[derive(Parser, Debug)]
-struct Arguments {
-    #[arg(short = 'g', default_value = "naive")]
-    guesser: GuesserType,
+fn daily<const N: usize>(
+    dictionary: Dictionary<N>,
+    guessers: &GuesserRegistry<N>,
+    guesser: &str,
+    puzzle: Puzzle,
+    reveal: bool,
+    play: bool,
+    solve: bool,
+) -> Result<(), Box<dyn Error>> {
+    const MAX_GUESSES: usize = 20;
+
+    let schedule = dictionary
+        .schedule
+        .as_ref()
+        .ok_or("the dictionary has no schedule of daily puzzles")?;
+    let (number, date) = schedule.resolve(puzzle)?;
+    let answer = schedule
+        .answer(number)
+        .ok_or_else(|| format!("the schedule has no puzzle #{number}"))?;
 
-    #[arg(short = 'd', value_name = "FILE", value_hint = ValueHint::FilePath)]
-    dictionary: std::path::PathBuf,
+    println!("puzzle #{number} of {date}");
+    if reveal {
+        println!("answer: {}", answer.to_string().red());
+    }
+
+    if play {
+        Play::new(dictionary.valid, FixedHost::new(answer)).run();
+    } else if solve {
+        // the answer may be in the schedule only, or excluded as a past answer
+        let mut answers = dictionary.answers;
+        if !answers.contains(&answer) {
+            answers.push(answer);
+        }
+        let pattern_cache = Pattern::prepare_all(&dictionary.valid, &answers);
+        let patterns = play::auto_play(
+            guessers.get(guesser)?.guesser(),
+            &dictionary.valid,
+            &answers,
+            &pattern_cache,
+            &mut FixedHost::new(answer),
+            MAX_GUESSES,
+        );
+        for pattern in &patterns {
+            match reveal {
+                true => println!("{pattern}"),
+                false => println!("{}", share::row_to_emoji(&pattern.colors())),
+            }
+        }
+        match patterns.last() {
+            Some(pattern) if pattern.is_solved() => {
+                println!("solved in {} guesses", patterns.len())
+            }
+            _ => println!("not solved in {MAX_GUESSES} guesses"),
+        }
+    }
+    Ok(())
+}
+
+fn record_answer<const N: usize>(
+    dictionary: &Dictionary<N>,
+    path: &Path,
+    answer: &str,
+    date: NaiveDate,
+) -> Result<(), Box<dyn Error>> {
+    let answer = Word::from_str(answer).map_err(|_| format!("expecting {N} letters"))?;
+    if !dictionary.answers.contains(&answer) {
+        eprintln!("{}", format!("{answer} isn't among the answers").yellow());
+    }
+
+    let mut past_answers = PastAnswers::from_file(path)?;
+    if let Some(replaced) = past_answers.record(date, answer) {
+        eprintln!("{}", format!("replaced {replaced} on {date}").yellow());
+    }
+    past_answers.to_file(path)
 }
 
 fn main() {
-    let args = Arguments::parse();
+    let mut guessers = GuesserRegistry::<WORD_LENGTH>::default();
+
+    let mut command = Arguments::command()
+        .mut_arg("guesser", |arg| {
+            arg.value_parser(PossibleValuesParser::new(
+                guessers
+                    .iter()
+                    .map(|entry| PossibleValue::new(entry.name()).help(entry.description())),
+            ))
+        })
+        .after_help(guessers_help(&guessers));
+    let mut args =
+        Arguments::from_arg_matches(&command.get_matches_mut()).unwrap_or_else(|e| e.exit());
+    if args.json_rpc && args.command.is_some() {
+        let message = "--json-rpc can't be used with a subcommand";
+        command.error(ErrorKind::ArgumentConflict, message).exit();
+    }
 
-    let dictionary = Dictionary::from_file(args.dictionary).unwrap();
-    let guesser = match args.guesser {
-        GuesserType::Naive => GuesserWrapper::Naive(NaiveGuesser),
-        GuesserType::Bfs => GuesserWrapper::Bfs(BfsGuesser),
-    };
+    let resumed = args
+        .resume
+        .as_ref()
+        .map(|path| SavedGame::from_file(path).unwrap());
+    if let Some(saved) = &resumed {
+        let explicit = args.dictionary.as_ref().map(std::fs::canonicalize);
+        if explicit.is_some_and(|path| path.ok().as_ref() != Some(&saved.dictionary)) {
+            let message = format!(
+                "the saved game uses dictionary {}, leave out -d",
+                saved.dictionary.display()
+            );
+            command.error(ErrorKind::ArgumentConflict, message).exit();
+        }
+        args.dictionary = Some(saved.dictionary.clone());
+        args.guesser.clone_from(&saved.guesser);
+        args.lies = saved.lies;
+    }
+    // absolute, so that a saved game can be resumed from another directory
+    let dictionary_path = args.dictionary.unwrap();
+    let dictionary_path = std::fs::canonicalize(&dictionary_path).unwrap_or(dictionary_path);
 
-    let game = Game::<5>::new(dictionary.valid, dictionary.answers, guesser);
-    game.run();
+    if let Err(e) = guessers.get(&args.guesser) {
+        command.error(ErrorKind::InvalidValue, e).exit();
+    }
+
+    let guesser = guessers.get_mut(&args.guesser).unwrap().guesser_mut();
+    // the saved options first, so that -o can still change them
+    for (name, value) in resumed.iter().flat_map(|saved| &saved.options) {
+        if let Err(e) = guesser.set_option(name, value) {
+            command.error(ErrorKind::InvalidValue, e).exit();
+        }
+    }
+    for option in &args.options {
+        let result = match option.split_once('=') {
+            Some((name, value)) => guesser.set_option(name, value).map_err(|e| e.to_string()),
+            None => Err(format!("expected NAME=VALUE, found `{option}`")),
+        };
+        if let Err(message) = result {
+            command.error(ErrorKind::InvalidValue, message).exit();
+        }
+    }
+
+    let mut dictionary = Dictionary::from_file(&dictionary_path).unwrap();
+    if let (Some(path), Some(date)) = (&args.past_answers, args.exclude_used_before) {
+        let past_answers = PastAnswers::from_file(path).unwrap();
+        dictionary.exclude_answers(&past_answers.used_before(date));
+    }
+
+    let tui = matches!(args.command, Some(Command::Tui));
+    match args.command {
+        Some(Command::Book { output }) => {
+            build_opening_book(&dictionary, &guessers, &args.guesser, &output);
+            return;
+        }
+        Some(Command::Play { absurdle }) => {
+            let mut play = if absurdle {
+                Play::new(dictionary.valid, AbsurdleHost::new(dictionary.answers))
+            } else {
+                let host = FixedHost::random(&dictionary.answers);
+                Play::new(dictionary.valid, host)
+            };
+            play.run();
+            return;
+        }
+        Some(Command::Absurdle {
+            max_guesses,
+            beam_width,
+        }) => {
+            match absurdle::solve(
+                &dictionary.valid,
+                &dictionary.answers,
+                max_guesses,
+                beam_width,
+            ) {
+                Some(guesses) => println!("{}", guesses.iter().join(" -> ")),
+                None => println!("couldn't win in {max_guesses} guesses"),
+            }
+            return;
+        }
+        Some(Command::Daily {
+            puzzle,
+            reveal,
+            play,
+            solve,
+        }) => {
+            let guesser = &args.guesser;
+            if let Err(e) = daily(dictionary, &guessers, guesser, puzzle, reveal, play, solve) {
+                command.error(ErrorKind::InvalidValue, e).exit();
+            }
+            return;
+        }
+        Some(Command::Record { answer, date }) => {
+            let Some(path) = &args.past_answers else {
+                let message = "recording an answer requires --past-answers";
+                command
+                    .error(ErrorKind::MissingRequiredArgument, message)
+                    .exit();
+            };
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            if let Err(e) = record_answer(&dictionary, path, &answer, date) {
+                command.error(ErrorKind::InvalidValue, e).exit();
+            }
+            return;
+        }
+        Some(Command::Solve { patterns }) => {
+            if let Err(e) = solve(dictionary, &guessers, &args.guesser, args.lies, &patterns) {
+                command.error(ErrorKind::InvalidValue, e).exit();
+            }
+            return;
+        }
+        Some(Command::Serve { address }) => {
+            let name = dictionary_path
+                .file_stem()
+                .map_or("default".into(), |stem| stem.to_string_lossy());
+            let mut server =
+                Server::new(guessers, &args.guesser).with_dictionary(&name, dictionary);
+            if let Some(time_limit) = args.time_limit {
+                server = server.with_time_limit(time_limit);
+            }
+
+            let http = match tiny_http::Server::http(&address) {
+                Ok(http) => http,
+                Err(e) => command.error(ErrorKind::Io, e).exit(),
+            };
+            eprintln!("listening on http://{address}");
+            server.run(&http);
+            return;
+        }
+        Some(Command::Tui) | None => {}
+    }
+
+    let opening_book = args.book.map(|path| OpeningBook::from_file(path).unwrap());
+    if let Some(book) = &opening_book {
+        let guesser = guessers.get(&args.guesser).unwrap().guesser();
+        if !book.is_for(
+            &dictionary.valid,
+            &dictionary.answers,
+            &args.guesser,
+            guesser,
+        ) {
+            let warning =
+                "the opening book was built for another dictionary, guesser or options, ignoring";
+            eprintln!("{}", warning.yellow());
+        }
+    }
+
+    let mut game = Game::<WORD_LENGTH>::new(dictionary, guessers, &args.guesser)
+        .unwrap()
+        .with_lies(args.lies)
+        .with_dictionary_path(dictionary_path);
+    if let Some(time_limit) = args.time_limit {
+        game = game.with_time_limit(time_limit);
+    }
+    if let Some(book) = opening_book {
+        game = game.with_opening_book(book);
+    }
+    if let Some(saved) = resumed {
+        game = game.with_resumed(saved);
+    }
+    let interactive = !args.json_rpc && !tui && std::io::stdin().is_terminal();
+    if let Some(home) = std::env::var_os("HOME").filter(|_| interactive) {
+        game = game.with_history_file(PathBuf::from(home).join(".rustybovich_history"));
+    }
+    if args.json_rpc {
+        colored::control::set_override(false);
+        game.run_json_rpc(std::io::stdin().lock(), std::io::stdout().lock())
+            .unwrap();
+    } else if tui {
+        game.run_tui().unwrap();
+    } else {
+        game.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        Arguments::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("1.5").unwrap(), Duration::from_millis(1500));
+        for seconds in ["-1", "inf", "NaN", "1e400", "soon"] {
+            assert!(parse_seconds(seconds).is_err(), "{seconds}");
+        }
+    }
 }
@@ -0,0 +1,268 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    str::FromStr,
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    guesser::{option_values, Guesser},
+    words::{Pattern, PatternCache, Word},
+    Dictionary,
+};
+
+#[derive(Serialize, Deserialize)]
+struct RankedGuessSerialized {
+    word: String,
+    rank: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OpeningBookSerialized {
+    dictionary: String,
+    guesser: String,
+    /// Missing in books built before the options were saved.
+    #[serde(default)]
+    options: BTreeMap<String, String>,
+    first: RankedGuessSerialized,
+    second: BTreeMap<String, RankedGuessSerialized>,
+}
+
+/// The best first guess and the best second guess for every pattern the first one can get,
+/// precomputed for a dictionary and a guesser with its options.
+pub struct OpeningBook<const N: usize> {
+    pub dictionary: u64,
+    pub guesser: String,
+    /// Values of the guesser's options by name.
+    pub options: BTreeMap<String, String>,
+    pub first: (Word<N>, f32),
+    pub second: HashMap<Pattern<N>, (Word<N>, f32)>,
+}
+
+impl<const N: usize> OpeningBook<N> {
+    /// Ranks the openings with `guesser`, calling `on_progress(done, total)` after every second
+    /// guess. Returns `None` if the guesser couldn't make a guess.
+    pub fn build(
+        dictionary: &Dictionary<N>,
+        guesser_name: &str,
+        guesser: &dyn Guesser<N>,
+        pattern_cache: &PatternCache<N>,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Option<Self> {
+        let best = |possible_answers: &[Word<N>]| {
+            guesser
+                .rank_guesses(&dictionary.valid, possible_answers, pattern_cache, 0)
+                .first()
+                .copied()
+        };
+
+        let first = best(&dictionary.answers)?;
+        let solved = Pattern::from_guess(&first.0, &first.0);
+
+        let mut buckets = dictionary
+            .answers
+            .iter()
+            .cloned()
+            .into_group_map_by(|answer| Pattern::from_guess(&first.0, answer));
+        buckets.remove(&solved);
+        let total = buckets.len();
+
+        let second = buckets
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (pattern, possible_answers))| {
+                let second = best(&possible_answers);
+                on_progress(i + 1, total);
+                second.map(|second| (pattern, second))
+            })
+            .collect();
+
+        Some(Self {
+            dictionary: dictionary.fingerprint(),
+            guesser: guesser_name.to_string(),
+            options: option_values(guesser),
+            first,
+            second,
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(&path)?;
+        let reader = BufReader::new(file);
+        let OpeningBookSerialized {
+            dictionary,
+            guesser,
+            options,
+            first,
+            second,
+        } = serde_json::from_reader(reader)?;
+
+        let first = (Word::from_str(&first.word)?, first.rank);
+        let second = second
+            .into_iter()
+            .map(|(colors, guess)| {
+                let pattern = Pattern::from_description(&first.0.to_string(), &colors)?;
+                Ok((pattern, (Word::from_str(&guess.word)?, guess.rank)))
+            })
+            .collect::<Result<_, Box<dyn Error>>>()?;
+
+        Ok(Self {
+            dictionary: u64::from_str_radix(&dictionary, 16)?,
+            guesser,
+            options,
+            first,
+            second,
+        })
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let ranked = |(word, rank): &(Word<N>, f32)| RankedGuessSerialized {
+            word: word.to_string(),
+            rank: *rank,
+        };
+
+        let book = OpeningBookSerialized {
+            dictionary: format!("{:016x}", self.dictionary),
+            guesser: self.guesser.clone(),
+            options: self.options.clone(),
+            first: ranked(&self.first),
+            second: self
+                .second
+                .iter()
+                .map(|(pattern, guess)| (pattern.to_description(), ranked(guess)))
+                .collect(),
+        };
+
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &book)?;
+        Ok(())
+    }
+
+    /// Whether the book was built for these word lists and the guesser named `guesser_name`
+    /// with the options `guesser` has now.
+    pub fn is_for(
+        &self,
+        valid: &[Word<N>],
+        answers: &[Word<N>],
+        guesser_name: &str,
+        guesser: &dyn Guesser<N>,
+    ) -> bool {
+        self.guesser == guesser_name
+            && self.options == option_values(guesser)
+            && self.dictionary == fingerprint(valid, answers)
+    }
+
+    /// The book guess after the patterns `played` so far, if there is one.
+    pub fn guess(&self, played: &[Pattern<N>]) -> Option<(Word<N>, f32)> {
+        match played {
+            [] => Some(self.first),
+            [pattern] => self.second.get(pattern).copied(),
+            _ => None,
+        }
+    }
+}
+
+/// FNV-1a hash of the word lists. Unlike `DefaultHasher` it is stable between builds, so it can
+/// be stored in files.
+pub fn fingerprint<const N: usize>(valid: &[Word<N>], answers: &[Word<N>]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    valid
+        .iter()
+        .chain(std::iter::once(&Word::new(['\n'; N])))
+        .chain(answers.iter())
+        .flat_map(|word| word.word)
+        .flat_map(|c| (c as u32).to_le_bytes())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        guesser::{BfsGuesser, NaiveGuesser},
+        test_util::{dictionary, words},
+    };
+
+    #[test]
+    fn test_build_and_read() {
+        let dictionary = dictionary();
+
+        let mut progress = vec![];
+        let book = OpeningBook::build(
+            &dictionary,
+            "naive",
+            &NaiveGuesser,
+            &PatternCache::new(),
+            |done, total| progress.push((done, total)),
+        )
+        .unwrap();
+        let second_guesses = book.second.len();
+        assert_eq!(progress.last(), Some(&(second_guesses, second_guesses)));
+        let (valid, answers) = (&dictionary.valid, &dictionary.answers);
+        assert!(book.is_for(valid, answers, "naive", &NaiveGuesser));
+        assert!(!book.is_for(valid, answers, "bfs", &NaiveGuesser));
+        assert!(!book.is_for(answers, answers, "naive", &NaiveGuesser));
+
+        let path = std::env::temp_dir().join("rustybovich-test-opening-book.json");
+        book.to_file(&path).unwrap();
+        let read = OpeningBook::<5>::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.dictionary, book.dictionary);
+        assert_eq!(read.guesser, book.guesser);
+        assert_eq!(read.options, book.options);
+        assert_eq!(read.first, book.first);
+        assert_eq!(read.second, book.second);
+
+        let first = book.guess(&[]).unwrap().0;
+        for answer in &dictionary.answers {
+            let pattern = Pattern::from_guess(&first, answer);
+            if *answer != first {
+                assert!(book.guess(&[pattern]).is_some());
+            }
+            assert_eq!(book.guess(&[pattern, pattern]), None);
+        }
+    }
+
+    #[test]
+    fn test_options() {
+        let dictionary = Dictionary {
+            valid: words(&["crate", "trace", "below", "elbow"]),
+            answers: words(&["crate", "trace", "below", "elbow"]),
+            schedule: None,
+        };
+
+        let guesser = BfsGuesser::new(1, 2);
+        let book = OpeningBook::build(
+            &dictionary,
+            "bfs",
+            &guesser,
+            &PatternCache::new(),
+            |_, _| {},
+        )
+        .unwrap();
+        let (valid, answers) = (&dictionary.valid, &dictionary.answers);
+        assert!(book.is_for(valid, answers, "bfs", &guesser));
+        assert!(book.is_for(valid, answers, "bfs", &BfsGuesser::new(1, 2)));
+        assert!(!book.is_for(valid, answers, "bfs", &BfsGuesser::new(1, 5)));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let a = words(&["crate", "slate"]);
+        let b = words(&["trace"]);
+
+        assert_eq!(fingerprint(&a, &b), fingerprint(&a, &b));
+        assert_ne!(fingerprint(&a, &b), fingerprint(&b, &a));
+        assert_ne!(fingerprint(&a, &b), fingerprint(&a, &[]));
+    }
+}
@@ -0,0 +1,63 @@
+//! The parts of rayon the crate uses. Without the `parallel` feature, e.g. for WebAssembly, they
+//! are replaced by sequential versions with the same names.
+
+#[cfg(feature = "parallel")]
+pub use rayon::{current_num_threads, prelude::*};
+
+#[cfg(not(feature = "parallel"))]
+pub use sequential::*;
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    pub trait IntoParallelIterator {
+        type Iter: Iterator;
+
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<'a, T> IntoParallelIterator for &'a [T] {
+        type Iter = std::slice::Iter<'a, T>;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.iter()
+        }
+    }
+
+    pub trait ParallelSlice<T> {
+        fn par_iter(&self) -> std::slice::Iter<'_, T>;
+    }
+
+    impl<T> ParallelSlice<T> for [T] {
+        fn par_iter(&self) -> std::slice::Iter<'_, T> {
+            self.iter()
+        }
+    }
+
+    pub trait ParallelSliceMut<T> {
+        fn as_parallel_slice_mut(&mut self) -> &mut [T];
+
+        fn par_sort_unstable_by_key<K: Ord>(&mut self, key: impl FnMut(&T) -> K) {
+            self.as_parallel_slice_mut().sort_unstable_by_key(key);
+        }
+    }
+
+    impl<T> ParallelSliceMut<T> for [T] {
+        fn as_parallel_slice_mut(&mut self) -> &mut [T] {
+            self
+        }
+    }
+
+    pub trait ParallelExtend<T> {
+        fn par_extend(&mut self, iter: impl IntoIterator<Item = T>);
+    }
+
+    impl<T> ParallelExtend<T> for Vec<T> {
+        fn par_extend(&mut self, iter: impl IntoIterator<Item = T>) {
+            self.extend(iter);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use itertools::Itertools;
+
+use crate::words::{Pattern, Word};
+
+/// How a guess splits the possible answers by the pattern it gets, largest buckets first.
+pub struct Partition<const N: usize> {
+    pub buckets: Vec<(Pattern<N>, Vec<Word<N>>)>,
+}
+
+impl<const N: usize> Partition<N> {
+    pub fn new(guess: &Word<N>, possible_answers: &[Word<N>]) -> Self {
+        let mut buckets = possible_answers
+            .iter()
+            .cloned()
+            .into_group_map_by(|answer| Pattern::from_guess(guess, answer))
+            .into_iter()
+            .collect_vec();
+        buckets.sort_by_cached_key(|(pattern, bucket)| {
+            (std::cmp::Reverse(bucket.len()), pattern.to_description())
+        });
+
+        Self { buckets }
+    }
+
+    fn total(&self) -> usize {
+        self.buckets.iter().map(|(_, bucket)| bucket.len()).sum()
+    }
+
+    pub fn largest(&self) -> usize {
+        self.buckets.first().map_or(0, |(_, bucket)| bucket.len())
+    }
+
+    /// Number of answers left after the guess, on average over the possible answers.
+    pub fn expected_size(&self) -> f32 {
+        let squares: usize = self
+            .buckets
+            .iter()
+            .map(|(_, bucket)| bucket.len() * bucket.len())
+            .sum();
+        squares as f32 / self.total() as f32
+    }
+
+    /// Information the pattern gives about the answer, in bits.
+    pub fn entropy(&self) -> f32 {
+        let total = self.total() as f32;
+        self.buckets
+            .iter()
+            .map(|(_, bucket)| {
+                let p = bucket.len() as f32 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Probability that the answer is known after the guess, so the next one solves the game.
+    pub fn solve_probability(&self) -> f32 {
+        let known = self
+            .buckets
+            .iter()
+            .filter(|(_, bucket)| bucket.len() == 1)
+            .count();
+        known as f32 / self.total() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_partition() {
+        let answers = words(&["crate", "trace", "below", "bowel", "light"]);
+        let partition = Partition::new(&"slate".parse().unwrap(), &answers);
+
+        let sizes = partition
+            .buckets
+            .iter()
+            .map(|(pattern, bucket)| (pattern.to_description(), bucket.len()))
+            .collect_vec();
+        assert_eq!(
+            sizes,
+            [
+                (".y..y".to_string(), 2),
+                ("..ggg".to_string(), 1),
+                ("..gyg".to_string(), 1),
+                (".y.y.".to_string(), 1),
+            ]
+        );
+        assert_eq!(partition.largest(), 2);
+        assert!((partition.expected_size() - 7.0 / 5.0).abs() < 1e-6);
+        assert!((partition.solve_probability() - 3.0 / 5.0).abs() < 1e-6);
+        let entropy = -0.4 * 0.4f32.log2() - 3.0 * 0.2 * 0.2f32.log2();
+        assert!((partition.entropy() - entropy).abs() < 1e-6);
+    }
+}
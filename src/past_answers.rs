@@ -0,0 +1,105 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind},
+    path::Path,
+    str::FromStr,
+};
+
+use chrono::NaiveDate;
+
+use crate::words::Word;
+
+/// Answers of past daily puzzles by date, for games that never repeat an answer.
+pub struct PastAnswers<const N: usize> {
+    pub answers: BTreeMap<NaiveDate, Word<N>>,
+}
+
+impl<const N: usize> PastAnswers<N> {
+    /// Reads a JSON object mapping `YYYY-MM-DD` dates to answers. A missing file has no answers,
+    /// so that the first one can be recorded.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(Self {
+                    answers: BTreeMap::new(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let answers: BTreeMap<NaiveDate, String> = serde_json::from_reader(BufReader::new(file))?;
+
+        let answers = answers
+            .into_iter()
+            .map(|(date, answer)| match Word::from_str(&answer) {
+                Ok(word) => Ok((date, word)),
+                Err(_) => Err(format!("{date}: `{answer}` isn't a word of {N} letters")),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { answers })
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let answers: BTreeMap<_, _> = self
+            .answers
+            .iter()
+            .map(|(date, answer)| (date, answer.to_string()))
+            .collect();
+
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &answers)?;
+        Ok(())
+    }
+
+    /// Records the answer of the puzzle on `date`, returns the one it replaces.
+    pub fn record(&mut self, date: NaiveDate, answer: Word<N>) -> Option<Word<N>> {
+        self.answers.insert(date, answer)
+    }
+
+    /// Answers of the puzzles before `date`.
+    pub fn used_before(&self, date: NaiveDate) -> Vec<Word<N>> {
+        self.answers
+            .range(..date)
+            .map(|(_, answer)| *answer)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let path = std::env::temp_dir().join("rustybovich-test-past-answers.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut past = PastAnswers::<5>::from_file(&path).unwrap();
+        assert!(past.answers.is_empty());
+
+        assert_eq!(
+            past.record(date("2024-01-02"), "crate".parse().unwrap()),
+            None
+        );
+        past.record(date("2024-01-01"), "light".parse().unwrap());
+        past.record(date("2024-01-03"), "below".parse().unwrap());
+        past.to_file(&path).unwrap();
+
+        let read = PastAnswers::<5>::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.answers, past.answers);
+
+        assert_eq!(
+            read.used_before(date("2024-01-03")),
+            words(&["light", "crate"])
+        );
+        assert!(read.used_before(date("2024-01-01")).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+#[cfg(feature = "cli")]
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "cli")]
+use colored::*;
+#[cfg(feature = "cli")]
+use itertools::Itertools;
+
+use crate::{
+    guesser::Guesser,
+    words::{Pattern, PatternCache, Word},
+};
+
+/// The side of the game that knows the answer and colors the guesses.
+pub trait Host<const N: usize> {
+    fn respond(&mut self, guess: &Word<N>) -> Pattern<N>;
+
+    /// Answers still consistent with every response, shown when the player gives up.
+    fn reveal(&self) -> Vec<Word<N>>;
+}
+
+/// The regular host with an answer fixed from the start.
+pub struct FixedHost<const N: usize> {
+    answer: Word<N>,
+}
+
+impl<const N: usize> FixedHost<N> {
+    pub fn new(answer: Word<N>) -> Self {
+        Self { answer }
+    }
+
+    /// Picks the answer based on the current time.
+    pub fn random(answers: &[Word<N>]) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        Self::new(answers[nanos as usize % answers.len()])
+    }
+}
+
+impl<const N: usize> Host<N> for FixedHost<N> {
+    fn respond(&mut self, guess: &Word<N>) -> Pattern<N> {
+        Pattern::from_guess(guess, &self.answer)
+    }
+
+    fn reveal(&self) -> Vec<Word<N>> {
+        vec![self.answer]
+    }
+}
+
+/// Game where the user guesses and a [`Host`] colors the guesses.
+#[cfg(feature = "cli")]
+pub struct Play<const N: usize> {
+    valid: Vec<Word<N>>,
+    host: Box<dyn Host<N>>,
+}
+
+#[cfg(feature = "cli")]
+impl<const N: usize> Play<N> {
+    pub fn new(valid: Vec<Word<N>>, host: impl Host<N> + 'static) -> Self {
+        Self {
+            valid,
+            host: Box::new(host),
+        }
+    }
+
+    pub fn run(&mut self) {
+        println!("guess the word or {} to give up", ":exit".cyan());
+
+        let mut guesses = 0;
+        for line in std::io::stdin().lines().map(Result::unwrap) {
+            if line == ":exit" {
+                let answers = self.host.reveal().iter().join(", ");
+                println!("answer: {}", answers.red());
+                return;
+            }
+
+            let guess = match Word::from_str(&line) {
+                Ok(word) if self.valid.contains(&word) => word,
+                Ok(_) => {
+                    println!("no such word in the dictionary");
+                    continue;
+                }
+                Err(_) => {
+                    println!("expecting {} letters, found {}", N, line.chars().count());
+                    continue;
+                }
+            };
+
+            let pattern = self.host.respond(&guess);
+            guesses += 1;
+            println!("{pattern} {guesses}");
+
+            if pattern.is_solved() {
+                println!("solved in {guesses} guesses");
+                return;
+            }
+        }
+    }
+}
+
+/// Plays against `host` with the best guess of `guesser` every time. Returns the patterns the
+/// guesses got, the last one is solved unless `max_guesses` ran out or the answer of `host` isn't
+/// among `answers`.
+pub fn auto_play<const N: usize>(
+    guesser: &dyn Guesser<N>,
+    valid: &[Word<N>],
+    answers: &[Word<N>],
+    pattern_cache: &PatternCache<N>,
+    host: &mut dyn Host<N>,
+    max_guesses: usize,
+) -> Vec<Pattern<N>> {
+    let mut possible_answers = answers.to_vec();
+    let mut patterns = vec![];
+
+    while patterns.len() < max_guesses {
+        let guess = match possible_answers[..] {
+            [] => break,
+            [answer] => answer,
+            _ => match guesser
+                .rank_guesses(valid, &possible_answers, pattern_cache, 0)
+                .first()
+            {
+                Some((guess, _)) => *guess,
+                None => break,
+            },
+        };
+
+        let pattern = host.respond(&guess);
+        patterns.push(pattern);
+        if pattern.is_solved() {
+            break;
+        }
+        possible_answers = pattern.filter_words(&possible_answers);
+    }
+    patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{guesser::NaiveGuesser, test_util::words};
+
+    #[test]
+    fn test_auto_play() {
+        let words = words(&["crate", "trace", "below", "elbow", "light"]);
+        let answer = "elbow".parse().unwrap();
+
+        let patterns = auto_play(
+            &NaiveGuesser,
+            &words,
+            &words,
+            &PatternCache::new(),
+            &mut FixedHost::new(answer),
+            6,
+        );
+        assert!(patterns.last().unwrap().is_solved());
+        assert_eq!(patterns.last().unwrap().word(), answer);
+
+        let patterns = auto_play(
+            &NaiveGuesser,
+            &words,
+            &words,
+            &PatternCache::new(),
+            &mut FixedHost::new(answer),
+            0,
+        );
+        assert!(patterns.is_empty());
+    }
+
+    #[test]
+    fn test_auto_play_unknown_answer() {
+        let words = words(&["crate", "trace", "below", "elbow", "light"]);
+        // in the schedule of the daily puzzles only
+        let answer = "flows".parse().unwrap();
+
+        let patterns = auto_play(
+            &NaiveGuesser,
+            &words,
+            &words,
+            &PatternCache::new(),
+            &mut FixedHost::new(answer),
+            6,
+        );
+        assert!(!patterns.is_empty());
+        assert!(!patterns.last().unwrap().is_solved());
+    }
+}
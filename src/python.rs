@@ -0,0 +1,298 @@
+//! Python extension module, built with e.g. `maturin develop --release`, see `pyproject.toml`.
+//!
+//! Words are taken as `Word`s or strings and returned as strings, rankings as lists of
+//! `(word, rank)` tuples.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    guesser::{GuesserEntry, GuesserRegistry},
+    words::{Pattern, PatternCache, Word},
+    Dictionary, WORD_LENGTH as N,
+};
+
+fn parse_word(word: &str) -> PyResult<Word<N>> {
+    word.parse()
+        .map_err(|_| PyValueError::new_err(format!("{word} isn't a word of {N} letters")))
+}
+
+fn to_strings(words: &[Word<N>]) -> Vec<String> {
+    words.iter().map(Word::to_string).collect()
+}
+
+/// A word argument, either a `Word` or a string.
+struct WordArg(Word<N>);
+
+impl<'py> FromPyObject<'py> for WordArg {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match ob.downcast::<PyWord>() {
+            Ok(word) => Ok(Self(word.get().0)),
+            Err(_) => parse_word(&ob.extract::<String>()?).map(Self),
+        }
+    }
+}
+
+fn words(words: Vec<WordArg>) -> Vec<Word<N>> {
+    words.into_iter().map(|WordArg(word)| word).collect()
+}
+
+#[pyclass(name = "Word", frozen, eq, ord, hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct PyWord(Word<N>);
+
+#[pymethods]
+impl PyWord {
+    #[new]
+    fn new(word: &str) -> PyResult<Self> {
+        parse_word(word).map(Self)
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Word('{}')", self.0)
+    }
+}
+
+#[pyclass(name = "Dictionary", frozen)]
+struct PyDictionary(Dictionary<N>);
+
+#[pymethods]
+impl PyDictionary {
+    #[staticmethod]
+    fn from_file(path: PathBuf) -> PyResult<Self> {
+        Dictionary::from_file(path)
+            .map(Self)
+            .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    /// Reads a dictionary in the format of the files from a string.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Dictionary::from_json(json)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Every word allowed as a guess, sorted.
+    #[getter]
+    fn valid(&self) -> Vec<String> {
+        to_strings(&self.0.valid)
+    }
+
+    /// The words that can be answers, sorted.
+    #[getter]
+    fn answers(&self) -> Vec<String> {
+        to_strings(&self.0.answers)
+    }
+}
+
+#[pyclass(name = "Pattern", frozen, eq, hash)]
+#[derive(PartialEq, Eq, Hash)]
+struct PyPattern(Pattern<N>);
+
+#[pymethods]
+impl PyPattern {
+    /// The colors `word` got, e.g. `Pattern("crate", "..y.g")`.
+    #[new]
+    fn new(word: &str, colors: &str) -> PyResult<Self> {
+        Pattern::from_description(word, colors)
+            .map(Self)
+            .map_err(|_| {
+                let message = format!("`{word} {colors}` isn't a pattern of {N} letters");
+                PyValueError::new_err(message)
+            })
+    }
+
+    /// The colors `guess` gets if `answer` is the answer.
+    #[staticmethod]
+    fn from_guess(guess: WordArg, answer: WordArg) -> Self {
+        Self(Pattern::from_guess(&guess.0, &answer.0))
+    }
+
+    #[getter]
+    fn word(&self) -> PyWord {
+        PyWord(self.0.word())
+    }
+
+    #[getter]
+    fn colors(&self) -> String {
+        self.0.to_description()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.0.is_solved()
+    }
+
+    /// Whether `word` could be the answer if exactly `lies` letters are colored wrong.
+    #[pyo3(signature = (word, lies = 0))]
+    fn matches(&self, word: WordArg, lies: usize) -> bool {
+        self.0.is_consistent(&word.0, lies)
+    }
+
+    /// The `words` that could be the answer if exactly `lies` letters are colored wrong.
+    #[pyo3(signature = (words, lies = 0))]
+    fn filter(&self, words: Vec<WordArg>, lies: usize) -> Vec<String> {
+        to_strings(&self.0.filter_words_with_lies(&self::words(words), lies))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Pattern('{}', '{}')",
+            self.0.word(),
+            self.0.to_description()
+        )
+    }
+}
+
+/// One of the guessers of the command line tool, with its options.
+#[pyclass(name = "Guesser", frozen)]
+struct PyGuesser {
+    registry: GuesserRegistry<N>,
+    name: &'static str,
+}
+
+#[pymethods]
+impl PyGuesser {
+    /// The guesser named `name`, with the options given as keyword arguments, e.g.
+    /// `Guesser("bfs", max_depth=4)`.
+    #[new]
+    #[pyo3(signature = (name, **options))]
+    fn new(name: &str, options: Option<HashMap<String, Bound<'_, PyAny>>>) -> PyResult<Self> {
+        let mut registry = GuesserRegistry::default();
+        let entry = registry
+            .get_mut(name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        for (option, value) in options.unwrap_or_default() {
+            entry
+                .guesser_mut()
+                .set_option(&option, &value.str()?.to_cow()?)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        }
+
+        let name = entry.name();
+        Ok(Self { registry, name })
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[getter]
+    fn description(&self) -> &'static str {
+        self.entry().description()
+    }
+
+    /// The options as `(name, value)` tuples.
+    fn options(&self) -> Vec<(&'static str, String)> {
+        self.entry()
+            .guesser()
+            .options()
+            .into_iter()
+            .map(|option| (option.name, option.value))
+            .collect()
+    }
+
+    /// Ranks `valid_guesses` for finding the answer among `possible_answers`, best first, as
+    /// `(word, rank)` tuples with lower ranks being better. `lies` is the number of letters
+    /// colored wrong in every pattern.
+    #[pyo3(signature = (valid_guesses, possible_answers, lies = 0))]
+    fn rank_guesses(
+        &self,
+        py: Python<'_>,
+        valid_guesses: Vec<WordArg>,
+        possible_answers: Vec<WordArg>,
+        lies: usize,
+    ) -> PyResult<Vec<(String, f32)>> {
+        let valid_guesses = words(valid_guesses);
+        let possible_answers = words(possible_answers);
+        if possible_answers.is_empty() {
+            return Err(PyValueError::new_err("no possible words left"));
+        }
+
+        let guesser = self.entry().guesser();
+        // the ranking runs on every core, other Python threads can run meanwhile
+        let ranked = py.allow_threads(|| {
+            guesser.rank_guesses(
+                &valid_guesses,
+                &possible_answers,
+                &PatternCache::new(),
+                lies,
+            )
+        });
+        Ok(ranked
+            .into_iter()
+            .map(|(word, rank)| (word.to_string(), rank))
+            .collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Guesser('{}')", self.name)
+    }
+}
+
+impl PyGuesser {
+    fn entry(&self) -> &GuesserEntry<N> {
+        self.registry.get(self.name).unwrap()
+    }
+}
+
+/// The names and descriptions of the guessers, as `(name, description)` tuples.
+#[pyfunction]
+fn guessers() -> Vec<(&'static str, &'static str)> {
+    GuesserRegistry::<N>::default()
+        .iter()
+        .map(|entry| (entry.name(), entry.description()))
+        .collect()
+}
+
+#[pymodule]
+fn rustybovich(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyWord>()?;
+    module.add_class::<PyDictionary>()?;
+    module.add_class::<PyPattern>()?;
+    module.add_class::<PyGuesser>()?;
+    module.add_function(wrap_pyfunction!(guessers, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+
+    use super::*;
+
+    #[test]
+    fn test_module() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "rustybovich").unwrap();
+            rustybovich(&module).unwrap();
+            let globals = [("rb", module)].into_py_dict(py).unwrap();
+            py.run(
+                cr#"
+words = ["crate", "trace", "below", "elbow"]
+pattern = rb.Pattern.from_guess("light", rb.Word("below"))
+assert pattern == rb.Pattern("light", "y....")
+assert pattern.filter(words) == ["below", "elbow"]
+
+ranked = rb.Guesser("naive").rank_guesses(words, ["below", "elbow"])
+assert len(ranked) == 4 and isinstance(ranked[0], tuple)
+assert ranked[0][1] <= ranked[-1][1]
+assert [name for name, _ in rb.guessers()] == ["naive", "bfs"]
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
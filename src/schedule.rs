@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+use chrono::{Days, NaiveDate};
+
+use crate::words::Word;
+
+/// A daily puzzle, by its number or by its date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Puzzle {
+    Number(usize),
+    Date(NaiveDate),
+}
+
+impl FromStr for Puzzle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start_matches('#');
+        s.parse()
+            .map(Puzzle::Number)
+            .or_else(|_| s.parse().map(Puzzle::Date))
+            .map_err(|_| format!("expecting a puzzle number or a YYYY-MM-DD date, found `{s}`"))
+    }
+}
+
+/// Answers of the daily puzzles in order, puzzle 0 being on the `first` date.
+pub struct Schedule<const N: usize> {
+    pub first: NaiveDate,
+    pub answers: Vec<Word<N>>,
+}
+
+impl<const N: usize> Schedule<N> {
+    /// The number and the date of `puzzle`, an error if it is before the first one or too far
+    /// ahead for a date.
+    pub fn resolve(&self, puzzle: Puzzle) -> Result<(usize, NaiveDate), String> {
+        match puzzle {
+            Puzzle::Number(number) => {
+                let date = self
+                    .first
+                    .checked_add_days(Days::new(number as u64))
+                    .ok_or_else(|| format!("no such puzzle: #{number}"))?;
+                Ok((number, date))
+            }
+            Puzzle::Date(date) => {
+                let number = (date - self.first).num_days();
+                let number = usize::try_from(number)
+                    .map_err(|_| "the puzzle is before the first one".to_string())?;
+                Ok((number, date))
+            }
+        }
+    }
+
+    /// The answer of puzzle `number`, `None` if the schedule doesn't go that far.
+    pub fn answer(&self, number: usize) -> Option<Word<N>> {
+        self.answers.get(number).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_schedule() {
+        let schedule = Schedule::<5> {
+            first: "2021-06-19".parse().unwrap(),
+            answers: words(&["cigar", "rebut", "sissy"]),
+        };
+        let date = |date: &str| date.parse::<NaiveDate>().unwrap();
+
+        assert_eq!(Puzzle::from_str("#2"), Ok(Puzzle::Number(2)));
+        assert_eq!(
+            Puzzle::from_str("2021-06-20"),
+            Ok(Puzzle::Date(date("2021-06-20")))
+        );
+        assert!(Puzzle::from_str("yesterday").is_err());
+
+        assert_eq!(
+            schedule.resolve(Puzzle::Number(2)),
+            Ok((2, date("2021-06-21")))
+        );
+        assert!(schedule.resolve(Puzzle::Number(99999999999999)).is_err());
+        assert_eq!(
+            schedule.resolve(Puzzle::Date(date("2021-06-20"))),
+            Ok((1, date("2021-06-20")))
+        );
+        assert!(schedule.resolve(Puzzle::Date(date("2021-06-18"))).is_err());
+
+        assert_eq!(schedule.answer(1), Some("rebut".parse().unwrap()));
+        assert_eq!(schedule.answer(3), None);
+    }
+}
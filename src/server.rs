@@ -0,0 +1,441 @@
+use std::{collections::BTreeMap, error::Error, path::PathBuf, sync::Arc, time::Duration};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    guesser::{GuesserRegistry, RankBudget},
+    session::{Mode, SolverSession},
+    words::{Pattern, Word},
+    Dictionary,
+};
+
+const DEFAULT_TOP: usize = 10;
+
+/// Error response of the API, with its HTTP status.
+#[derive(Debug, PartialEq)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        Self {
+            status: 400,
+            message: message.to_string(),
+        }
+    }
+
+    fn not_found(message: impl ToString) -> Self {
+        Self {
+            status: 404,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LoadDictionary {
+    name: String,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct CreateSession {
+    dictionary: Option<String>,
+    #[serde(default)]
+    lies: usize,
+}
+
+#[derive(Serialize)]
+struct RankedGuess<const N: usize> {
+    word: Word<N>,
+    rank: f32,
+}
+
+/// A game being solved through the API. It keeps the dictionary it was created with when
+/// another one is loaded under the same name.
+struct Session<const N: usize> {
+    dictionary: String,
+    solver: SolverSession<'static, N>,
+}
+
+impl<const N: usize> Session<N> {
+    fn to_json(&self, id: u64) -> Value {
+        json!({
+            "id": id,
+            "dictionary": self.dictionary,
+            "lies": self.solver.lies(),
+            "patterns": self.solver.history().patterns(),
+            "candidates": self.solver.candidates().len(),
+        })
+    }
+}
+
+/// JSON API over HTTP for solving games: dictionaries are loaded by name, every session is a
+/// game with its own patterns, and guesses are ranked by any guesser of the registry.
+///
+/// | request | body | response |
+/// |---------|------|----------|
+/// | `GET /dictionaries` | | names and sizes of the loaded dictionaries |
+/// | `POST /dictionaries` | `{"name", "path"}` | the loaded dictionary |
+/// | `GET /guessers` | | names and descriptions of the guessers |
+/// | `POST /sessions` | `{"dictionary"?, "lies"?}` | the new session |
+/// | `GET /sessions/{id}` | | the session |
+/// | `DELETE /sessions/{id}` | | |
+/// | `POST /sessions/{id}/patterns` | `[{"word", "colors"}]` | the session |
+/// | `GET /sessions/{id}/candidates` | | the answers left |
+/// | `GET /sessions/{id}/ranking?guesser=&top=&hard=` | | the best guesses with their ranks |
+///
+/// Loading a dictionary under the name of a loaded one replaces it for the new sessions.
+pub struct Server<const N: usize> {
+    dictionaries: BTreeMap<String, Arc<Dictionary<N>>>,
+    /// Dictionary of the sessions created without one.
+    default_dictionary: Option<String>,
+    guessers: GuesserRegistry<N>,
+    default_guesser: String,
+    sessions: BTreeMap<u64, Session<N>>,
+    next_session: u64,
+    time_limit: Option<Duration>,
+}
+
+impl<const N: usize> Server<N> {
+    pub fn new(guessers: GuesserRegistry<N>, default_guesser: &str) -> Self {
+        Self {
+            dictionaries: BTreeMap::new(),
+            default_dictionary: None,
+            guessers,
+            default_guesser: default_guesser.to_string(),
+            sessions: BTreeMap::new(),
+            next_session: 1,
+            time_limit: None,
+        }
+    }
+
+    /// Adds a dictionary, the first one added is the default for new sessions.
+    pub fn with_dictionary(mut self, name: &str, dictionary: Dictionary<N>) -> Self {
+        self.add_dictionary(name, dictionary);
+        self
+    }
+
+    /// Stops ranking guesses after `time_limit`, the response then has the best ones found so far.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    fn add_dictionary(&mut self, name: &str, dictionary: Dictionary<N>) {
+        self.dictionaries
+            .insert(name.to_string(), Arc::new(dictionary));
+        self.default_dictionary
+            .get_or_insert_with(|| name.to_string());
+    }
+
+    /// Answers the requests of `http` one at a time until it is unblocked.
+    pub fn run(&mut self, http: &tiny_http::Server) {
+        for request in http.incoming_requests() {
+            if let Err(e) = self.respond(request) {
+                eprintln!("couldn't respond: {e}");
+            }
+        }
+    }
+
+    fn respond(&mut self, mut request: Request) -> Result<(), Box<dyn Error>> {
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body)?;
+
+        let (status, json) = match self.handle(request.method(), request.url(), &body) {
+            Ok((status, json)) => (status, json),
+            Err(ApiError { status, message }) => (status, json!({ "error": message })),
+        };
+
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = match json {
+            Value::Null => Response::from_string(""),
+            json => Response::from_string(json.to_string()).with_header(content_type),
+        };
+        request.respond(response.with_status_code(status))?;
+        Ok(())
+    }
+
+    /// Status and JSON body of the response to a request, `Value::Null` for no body.
+    fn handle(&mut self, method: &Method, url: &str, body: &str) -> Result<(u16, Value), ApiError> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        match (method, &segments[..]) {
+            (Method::Get, ["dictionaries"]) => Ok((200, self.dictionaries_json())),
+            (Method::Post, ["dictionaries"]) => {
+                let LoadDictionary { name, path } = parse_body(body)?;
+                let dictionary = Dictionary::from_file(&path).map_err(|e| {
+                    ApiError::bad_request(format!("couldn't load {}: {e}", path.display()))
+                })?;
+                let json = dictionary_json(&name, &dictionary);
+                self.add_dictionary(&name, dictionary);
+                Ok((201, json))
+            }
+            (Method::Get, ["guessers"]) => {
+                let guessers: Vec<_> = self
+                    .guessers
+                    .iter()
+                    .map(
+                        |entry| json!({ "name": entry.name(), "description": entry.description() }),
+                    )
+                    .collect();
+                Ok((200, json!(guessers)))
+            }
+            (Method::Post, ["sessions"]) => {
+                let CreateSession { dictionary, lies } = parse_body(body)?;
+                let dictionary = dictionary
+                    .or_else(|| self.default_dictionary.clone())
+                    .ok_or_else(|| ApiError::bad_request("no dictionary is loaded"))?;
+                let shared = self
+                    .dictionaries
+                    .get(&dictionary)
+                    .ok_or_else(|| ApiError::not_found(format!("no dictionary `{dictionary}`")))?;
+
+                let id = self.next_session;
+                self.next_session += 1;
+                let session = Session {
+                    solver: SolverSession::shared(Arc::clone(shared), lies),
+                    dictionary,
+                };
+                let json = session.to_json(id);
+                self.sessions.insert(id, session);
+                Ok((201, json))
+            }
+            (method, ["sessions", id, rest @ ..]) => {
+                let id = id
+                    .parse()
+                    .ok()
+                    .filter(|id| self.sessions.contains_key(id))
+                    .ok_or_else(|| ApiError::not_found(format!("no session `{id}`")))?;
+                self.handle_session(method, id, rest, query, body)
+            }
+            _ => Err(ApiError::not_found(format!("no endpoint {method} {path}"))),
+        }
+    }
+
+    fn handle_session(
+        &mut self,
+        method: &Method,
+        id: u64,
+        rest: &[&str],
+        query: &str,
+        body: &str,
+    ) -> Result<(u16, Value), ApiError> {
+        let session = self.sessions.get_mut(&id).unwrap();
+        match (method, rest) {
+            (Method::Get, []) => Ok((200, session.to_json(id))),
+            (Method::Delete, []) => {
+                self.sessions.remove(&id);
+                Ok((204, Value::Null))
+            }
+            (Method::Post, ["patterns"]) => {
+                let patterns: Vec<Pattern<N>> = parse_body(body)?;
+                // all or none of the patterns are applied
+                let steps = session.solver.history().steps().len();
+                for pattern in patterns {
+                    if let Err(e) = session.solver.apply(pattern) {
+                        while session.solver.history().steps().len() > steps {
+                            session.solver.undo();
+                        }
+                        return Err(ApiError::bad_request(e));
+                    }
+                }
+                Ok((200, session.to_json(id)))
+            }
+            (Method::Get, ["candidates"]) => Ok((200, json!(session.solver.candidates()))),
+            (Method::Get, ["ranking"]) => self.ranking(id, query).map(|json| (200, json)),
+            _ => Err(ApiError::not_found(format!(
+                "no endpoint {method} /sessions/{id}/{}",
+                rest.join("/")
+            ))),
+        }
+    }
+
+    fn ranking(&mut self, id: u64, query: &str) -> Result<Value, ApiError> {
+        let mut guesser = self.default_guesser.as_str();
+        let mut top = DEFAULT_TOP;
+        let mut hard = false;
+        for (name, value) in query.split('&').filter_map(|param| param.split_once('=')) {
+            let invalid = || ApiError::bad_request(format!("invalid value `{value}` for `{name}`"));
+            match name {
+                "guesser" => guesser = value,
+                "top" => top = value.parse().map_err(|_| invalid())?,
+                "hard" => hard = value.parse().map_err(|_| invalid())?,
+                _ => return Err(ApiError::bad_request(format!("unknown parameter `{name}`"))),
+            }
+        }
+
+        let entry = self.guessers.get(guesser).map_err(ApiError::not_found)?;
+        let solver = &mut self.sessions.get_mut(&id).unwrap().solver;
+        solver.set_mode(match hard {
+            true => Mode::Hard,
+            false => Mode::Normal,
+        });
+        let budget = match self.time_limit {
+            Some(time_limit) => RankBudget::unlimited().with_timeout(time_limit),
+            None => RankBudget::unlimited(),
+        };
+        let ranking = solver
+            .rank(entry.guesser(), &budget, &mut |_| {})
+            .map_err(ApiError::bad_request)?;
+
+        let guesses: Vec<_> = ranking
+            .guesses
+            .into_iter()
+            .take(top)
+            .map(|(word, rank)| RankedGuess { word, rank })
+            .collect();
+        Ok(json!({
+            "guesser": entry.name(),
+            "complete": ranking.complete,
+            "guesses": guesses,
+        }))
+    }
+
+    fn dictionaries_json(&self) -> Value {
+        let dictionaries: Vec<_> = self
+            .dictionaries
+            .iter()
+            .map(|(name, dictionary)| dictionary_json(name, dictionary))
+            .collect();
+        json!(dictionaries)
+    }
+}
+
+fn dictionary_json<const N: usize>(name: &str, dictionary: &Dictionary<N>) -> Value {
+    json!({
+        "name": name,
+        "valid": dictionary.valid.len(),
+        "answers": dictionary.answers.len(),
+    })
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("invalid body: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{SocketAddr, TcpStream},
+    };
+
+    use super::*;
+    use crate::test_util::dictionary;
+
+    fn request(address: SocketAddr, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{method} {url} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line).unwrap();
+        let status = status_line.split(' ').nth(1).unwrap().parse().unwrap();
+
+        let mut response = String::new();
+        reader.read_to_string(&mut response).unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap_or(Value::Null))
+    }
+
+    /// Address of a server with the test dictionary as `test`.
+    fn start_server() -> SocketAddr {
+        let mut server =
+            Server::new(GuesserRegistry::default(), "naive").with_dictionary("test", dictionary());
+        let http = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let address = http.server_addr().to_ip().unwrap();
+        std::thread::spawn(move || server.run(&http));
+        address
+    }
+
+    #[test]
+    fn test_session() {
+        let address = start_server();
+
+        let (status, dictionaries) = request(address, "GET", "/dictionaries", "");
+        assert_eq!(status, 200);
+        assert_eq!(
+            dictionaries,
+            json!([{"name": "test", "valid": 6, "answers": 4}])
+        );
+
+        let (status, session) = request(address, "POST", "/sessions", "{}");
+        assert_eq!(status, 201);
+        assert_eq!(session["candidates"], 4);
+        let url = format!("/sessions/{}", session["id"]);
+
+        let patterns = r#"[{"word": "slate", "colors": "..ggg"}]"#;
+        let (status, session) = request(address, "POST", &format!("{url}/patterns"), patterns);
+        assert_eq!(status, 200);
+        assert_eq!(
+            session["patterns"],
+            json!([{"word": "slate", "colors": "..ggg"}])
+        );
+
+        let (_, candidates) = request(address, "GET", &format!("{url}/candidates"), "");
+        assert_eq!(candidates, json!(["crate"]));
+
+        let (status, ranking) = request(address, "GET", &format!("{url}/ranking?top=2"), "");
+        assert_eq!(status, 200);
+        assert_eq!(ranking["guesser"], "naive");
+        assert_eq!(ranking["guesses"].as_array().unwrap().len(), 2);
+
+        let (status, error) = request(address, "GET", &format!("{url}/ranking?guesser=x"), "");
+        assert_eq!(status, 404);
+        assert_eq!(error["error"], "unknown guesser `x`");
+
+        let patterns =
+            r#"[{"word": "trace", "colors": "....."}, {"word": "xxxxx", "colors": "....."}]"#;
+        let (status, error) = request(address, "POST", &format!("{url}/patterns"), patterns);
+        assert_eq!(status, 400);
+        assert_eq!(error["error"], "xxxxx isn't in the dictionary");
+        let (_, candidates) = request(address, "GET", &format!("{url}/candidates"), "");
+        assert_eq!(candidates, json!(["crate"]));
+
+        assert_eq!(request(address, "DELETE", &url, "").0, 204);
+        assert_eq!(request(address, "GET", &url, "").0, 404);
+    }
+
+    #[test]
+    fn test_reload_dictionary() {
+        let address = start_server();
+        let (_, session) = request(address, "POST", "/sessions", "{}");
+        let url = format!("/sessions/{}", session["id"]);
+        let patterns = r#"[{"word": "slate", "colors": "..ggg"}]"#;
+        request(address, "POST", &format!("{url}/patterns"), patterns);
+
+        let path = std::env::temp_dir().join(format!("reload-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"valid": ["spine"], "answers": ["spine"]}"#).unwrap();
+        let body = json!({"name": "test", "path": path}).to_string();
+        let (status, _) = request(address, "POST", "/dictionaries", &body);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(status, 201);
+
+        // the session keeps its dictionary, new ones get the reloaded one
+        let (status, session) = request(address, "GET", &url, "");
+        assert_eq!(status, 200);
+        assert_eq!(session["candidates"], 1);
+        let (_, candidates) = request(address, "GET", &format!("{url}/candidates"), "");
+        assert_eq!(candidates, json!(["crate"]));
+        let (status, _) = request(address, "GET", &format!("{url}/ranking"), "");
+        assert_eq!(status, 200);
+
+        let (_, session) = request(address, "POST", "/sessions", "{}");
+        assert_eq!(session["candidates"], 1);
+        let url = format!("/sessions/{}", session["id"]);
+        let (status, _) = request(address, "POST", &format!("{url}/patterns"), patterns);
+        assert_eq!(status, 400);
+    }
+}
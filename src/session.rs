@@ -0,0 +1,520 @@
+//! State of a game being solved, for the front-ends to share: the guesses and other steps so
+//! far, with undo and redo, and the answers they leave.
+
+use std::{ops::Deref, sync::Arc};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    guesser::{Guesser, RankBudget, RankProgress, Ranking},
+    partition::Partition,
+    words::{Pattern, PatternCache, Word},
+    Dictionary,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Only the possible answers are ranked as guesses.
+    Hard,
+    Normal,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step<const N: usize> {
+    Guess(Pattern<N>),
+    /// Words known not to be the answer.
+    Ban(Vec<Word<N>>),
+    /// Words that may be the answer and may be guessed though the dictionary doesn't have them.
+    Allow(Vec<Word<N>>),
+}
+
+impl<const N: usize> std::fmt::Display for Step<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Guess(pattern) => write!(f, "{pattern}"),
+            Step::Ban(words) => write!(f, "ban {}", words.iter().join(" ")),
+            Step::Allow(words) => write!(f, "allow {}", words.iter().join(" ")),
+        }
+    }
+}
+
+/// Steps applied during a game along with the undone ones that can be redone.
+#[derive(Default)]
+pub struct History<const N: usize> {
+    steps: Vec<Step<N>>,
+    undone: Vec<Step<N>>,
+}
+
+impl<const N: usize> History<N> {
+    fn push(&mut self, step: Step<N>) {
+        self.steps.push(step);
+        self.undone.clear();
+    }
+
+    fn undo(&mut self) -> Option<&Step<N>> {
+        let step = self.steps.pop()?;
+        self.undone.push(step);
+        self.undone.last()
+    }
+
+    fn redo(&mut self) -> Option<&Step<N>> {
+        let step = self.undone.pop()?;
+        self.steps.push(step);
+        self.steps.last()
+    }
+
+    /// Removes the `n`-th step, counting from 1.
+    fn drop(&mut self, n: usize) -> Option<Step<N>> {
+        (1..=self.steps.len())
+            .contains(&n)
+            .then(|| self.steps.remove(n - 1))
+    }
+
+    pub fn steps(&self) -> &[Step<N>] {
+        &self.steps
+    }
+
+    /// Patterns of the guesses, without the bans and allows.
+    pub fn patterns(&self) -> Vec<Pattern<N>> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Guess(pattern) => Some(*pattern),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether words have been banned or allowed, so the dictionary is not the original one.
+    pub fn changes_dictionary(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| !matches!(step, Step::Guess(_)))
+    }
+
+    /// `valid` with the allowed words added.
+    pub fn valid(&self, valid: &[Word<N>]) -> Vec<Word<N>> {
+        let mut valid = valid.to_vec();
+        for step in &self.steps {
+            if let Step::Allow(words) = step {
+                valid.extend(
+                    words
+                        .iter()
+                        .filter(|word| !valid.contains(word))
+                        .collect_vec(),
+                );
+            }
+        }
+        valid
+    }
+
+    /// Answers left after every step. Allowed words are added unless a guess before them rules
+    /// them out, so the order of the steps doesn't matter for them.
+    pub fn replay(&self, answers: &[Word<N>], lies: usize) -> Vec<Vec<Word<N>>> {
+        let mut patterns = vec![];
+        self.steps
+            .iter()
+            .scan(answers.to_vec(), |possible_answers, step| {
+                match step {
+                    Step::Guess(pattern) => {
+                        *possible_answers = pattern.filter_words_with_lies(possible_answers, lies);
+                        patterns.push(*pattern);
+                    }
+                    Step::Ban(words) => possible_answers.retain(|answer| !words.contains(answer)),
+                    Step::Allow(words) => {
+                        let allowed = words
+                            .iter()
+                            .filter(|word| !possible_answers.contains(word))
+                            .filter(|word| {
+                                patterns
+                                    .iter()
+                                    .all(|pattern| pattern.is_consistent(word, lies))
+                            })
+                            .collect_vec();
+                        possible_answers.extend(allowed);
+                    }
+                }
+                Some(possible_answers.clone())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SessionError {
+    /// The guess isn't in the dictionary and hasn't been allowed.
+    UnknownWord(String),
+    NoCandidates,
+}
+
+impl std::error::Error for SessionError {}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::UnknownWord(word) => write!(f, "{word} isn't in the dictionary"),
+            SessionError::NoCandidates => write!(f, "no possible words left"),
+        }
+    }
+}
+
+/// The dictionary of a session, borrowed or shared with the session's owner.
+enum SessionDictionary<'a, const N: usize> {
+    Borrowed(&'a Dictionary<N>),
+    Shared(Arc<Dictionary<N>>),
+}
+
+impl<const N: usize> Deref for SessionDictionary<'_, N> {
+    type Target = Dictionary<N>;
+
+    fn deref(&self) -> &Dictionary<N> {
+        match self {
+            SessionDictionary::Borrowed(dictionary) => dictionary,
+            SessionDictionary::Shared(dictionary) => dictionary,
+        }
+    }
+}
+
+/// A game being solved with the words of a dictionary, the REPL and the other front-ends only
+/// read input and show the results. Front-ends keeping a session between calls, where it can't
+/// borrow the dictionary, share it with [`SolverSession::shared`].
+pub struct SolverSession<'a, const N: usize> {
+    dictionary: SessionDictionary<'a, N>,
+    lies: usize,
+    pattern_cache: PatternCache<'a, N>,
+    mode: Mode,
+    history: History<N>,
+    candidates: Vec<Word<N>>,
+}
+
+impl<const N: usize> SolverSession<'static, N> {
+    /// Like [`SolverSession::new`], for a session owning a share of `dictionary`.
+    pub fn shared(dictionary: Arc<Dictionary<N>>, lies: usize) -> Self {
+        Self::with_dictionary(SessionDictionary::Shared(dictionary), lies)
+    }
+}
+
+impl<'a, const N: usize> SolverSession<'a, N> {
+    /// A new game where every answer of `dictionary` is possible. `lies` is the number of letters
+    /// colored wrong in every pattern, 0 for regular games.
+    pub fn new(dictionary: &'a Dictionary<N>, lies: usize) -> Self {
+        Self::with_dictionary(SessionDictionary::Borrowed(dictionary), lies)
+    }
+
+    fn with_dictionary(dictionary: SessionDictionary<'a, N>, lies: usize) -> Self {
+        Self {
+            candidates: dictionary.answers.clone(),
+            dictionary,
+            lies,
+            pattern_cache: PatternCache::new(),
+            mode: Mode::Normal,
+            history: History::default(),
+        }
+    }
+
+    /// Prepares the patterns of every guess and answer of the dictionary for ranking, which takes
+    /// a while and a lot of memory for big dictionaries. Sessions of a shared dictionary compute
+    /// the patterns as they rank.
+    pub fn with_prepared_patterns(mut self) -> Self {
+        if let SessionDictionary::Borrowed(dictionary) = self.dictionary {
+            self.pattern_cache = Pattern::prepare_all(&dictionary.valid, &dictionary.answers);
+        }
+        self
+    }
+
+    pub fn dictionary(&self) -> &Dictionary<N> {
+        &self.dictionary
+    }
+
+    pub fn lies(&self) -> usize {
+        self.lies
+    }
+
+    pub fn pattern_cache(&self) -> &PatternCache<'a, N> {
+        &self.pattern_cache
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    pub fn history(&self) -> &History<N> {
+        &self.history
+    }
+
+    /// The answers left after the steps so far.
+    pub fn candidates(&self) -> &[Word<N>] {
+        &self.candidates
+    }
+
+    /// The words of the dictionary with the allowed ones.
+    pub fn valid(&self) -> Vec<Word<N>> {
+        self.history.valid(&self.dictionary.valid)
+    }
+
+    /// The words to rank as guesses in the current mode.
+    pub fn valid_guesses(&self) -> Vec<Word<N>> {
+        match self.mode {
+            Mode::Hard => self.candidates.clone(),
+            Mode::Normal => self.valid(),
+        }
+    }
+
+    /// Answers left after every step, see [`History::replay`].
+    pub fn replay(&self) -> Vec<Vec<Word<N>>> {
+        self.history.replay(&self.dictionary.answers, self.lies)
+    }
+
+    /// Applies the colors a guess got.
+    pub fn apply(&mut self, pattern: Pattern<N>) -> Result<(), SessionError> {
+        if !self.valid().contains(&pattern.word()) {
+            return Err(SessionError::UnknownWord(pattern.word().to_string()));
+        }
+
+        self.candidates = pattern.filter_words_with_lies(&self.candidates, self.lies);
+        self.history.push(Step::Guess(pattern));
+        Ok(())
+    }
+
+    /// Rules out those of `words` that may still be the answer, returns the others.
+    pub fn ban(&mut self, words: Vec<Word<N>>) -> Vec<Word<N>> {
+        let (banned, not_possible): (Vec<_>, Vec<_>) = words
+            .into_iter()
+            .partition(|word| self.candidates.contains(word));
+        if !banned.is_empty() {
+            self.history.push(Step::Ban(banned));
+            self.update();
+        }
+        not_possible
+    }
+
+    /// Adds `words` to the guesses and the possible answers.
+    pub fn allow(&mut self, words: Vec<Word<N>>) {
+        self.history.push(Step::Allow(words));
+        self.update();
+    }
+
+    /// Takes back the last step, returns it unless there were none.
+    pub fn undo(&mut self) -> Option<&Step<N>> {
+        self.history.undo()?;
+        self.update();
+        self.history.undone.last()
+    }
+
+    /// Applies the last undone step again, returns it unless there were none.
+    pub fn redo(&mut self) -> Option<&Step<N>> {
+        self.history.redo()?;
+        self.update();
+        self.history.steps.last()
+    }
+
+    /// Removes the `n`-th step, counting from 1, and returns it.
+    pub fn drop(&mut self, n: usize) -> Option<Step<N>> {
+        let step = self.history.drop(n)?;
+        self.update();
+        Some(step)
+    }
+
+    /// Starts a new game in normal mode.
+    pub fn reset(&mut self) {
+        self.restore(Mode::Normal, vec![]);
+    }
+
+    /// Replaces the game with `steps` applied in `mode`, e.g. a saved one, with nothing to redo.
+    pub fn restore(&mut self, mode: Mode, steps: Vec<Step<N>>) {
+        self.mode = mode;
+        self.history = History {
+            steps,
+            undone: vec![],
+        };
+        self.update();
+    }
+
+    /// Recomputes the candidates after the history changed.
+    fn update(&mut self) {
+        self.candidates = self
+            .replay()
+            .pop()
+            .unwrap_or_else(|| self.dictionary.answers.clone());
+    }
+
+    /// Ranks the guesses of the current mode with `guesser` until `budget` is exhausted, see
+    /// [`Guesser::rank_guesses_within`].
+    pub fn rank(
+        &self,
+        guesser: &dyn Guesser<N>,
+        budget: &RankBudget,
+        on_progress: &mut RankProgress<N>,
+    ) -> Result<Ranking<N>, SessionError> {
+        if self.candidates.is_empty() {
+            return Err(SessionError::NoCandidates);
+        }
+
+        Ok(guesser.rank_guesses_within(
+            &self.valid_guesses(),
+            &self.candidates,
+            &self.pattern_cache,
+            self.lies,
+            budget,
+            on_progress,
+        ))
+    }
+
+    /// How `guess` would split the candidates by the patterns it can get.
+    pub fn split(&self, guess: &Word<N>) -> Partition<N> {
+        Partition::new(guess, &self.candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        guesser::NaiveGuesser,
+        test_util::{dictionary, words},
+    };
+
+    fn pattern(word: &str, colors: &str) -> Pattern<5> {
+        Pattern::from_description(word, colors).unwrap()
+    }
+
+    #[test]
+    fn test_history_undo_redo() {
+        let mut history = History::default();
+        let (a, b, c) = (
+            Step::Guess(pattern("crate", "..y..")),
+            Step::Ban(words(&["light"])),
+            Step::Guess(pattern("below", "ggggg")),
+        );
+
+        history.push(a.clone());
+        history.push(b.clone());
+        assert_eq!(history.undo(), Some(&b));
+        assert_eq!(history.undo(), Some(&a));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&a));
+        assert_eq!(history.steps(), std::slice::from_ref(&a));
+
+        history.push(c.clone());
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.steps(), [a, c]);
+    }
+
+    #[test]
+    fn test_history_drop_and_replay() {
+        let answers = words(&["crate", "trace", "below", "elbow", "light"]);
+
+        let answer = "trace".parse().unwrap();
+        let first = Pattern::from_guess(&"slate".parse().unwrap(), &answer);
+        let second = Pattern::from_guess(&"crate".parse().unwrap(), &answer);
+
+        let mut history = History::default();
+        history.push(Step::Guess(first));
+        history.push(Step::Guess(second));
+
+        let left_first = first.filter_words(&answers);
+        let left_second = second.filter_words(&left_first);
+        assert!(left_second.contains(&answer));
+        assert_eq!(history.replay(&answers, 0), vec![left_first, left_second]);
+
+        assert_eq!(history.drop(3), None);
+        assert_eq!(history.drop(0), None);
+        assert_eq!(history.drop(1), Some(Step::Guess(first)));
+        assert_eq!(
+            history.replay(&answers, 0),
+            vec![second.filter_words(&answers)]
+        );
+    }
+
+    #[test]
+    fn test_history_ban_and_allow() {
+        let answers = words(&["crate", "trace", "below"]);
+        let valid = words(&["crate", "trace", "below", "slate"]);
+
+        let mut history = History::default();
+        history.push(Step::Ban(words(&["crate"])));
+        history.push(Step::Allow(words(&["tract", "slate"])));
+        history.push(Step::Guess(pattern("trace", "gggg.")));
+
+        assert_eq!(
+            history.replay(&answers, 0),
+            vec![
+                words(&["trace", "below"]),
+                words(&["trace", "below", "tract", "slate"]),
+                words(&["tract"]),
+            ]
+        );
+        assert_eq!(
+            history.valid(&valid),
+            words(&["crate", "trace", "below", "slate", "tract"])
+        );
+        assert_eq!(history.patterns(), [pattern("trace", "gggg.")]);
+        assert!(history.changes_dictionary());
+    }
+
+    #[test]
+    fn test_history_allow_after_guess() {
+        let answers = words(&["crate", "trace", "below"]);
+        let guess = Step::Guess(pattern("trace", "gggg."));
+        let allow = Step::Allow(words(&["tract", "slate"]));
+
+        let mut history = History::default();
+        history.push(guess.clone());
+        history.push(allow.clone());
+        // slate contradicts the guess
+        assert_eq!(history.replay(&answers, 0).pop(), Some(words(&["tract"])));
+
+        let mut reordered = History::default();
+        reordered.push(allow);
+        reordered.push(guess);
+        assert_eq!(
+            reordered.replay(&answers, 0).pop(),
+            history.replay(&answers, 0).pop()
+        );
+    }
+
+    #[test]
+    fn test_session() {
+        let dictionary = dictionary();
+        let mut session = SolverSession::new(&dictionary, 0);
+
+        assert_eq!(
+            session.apply(pattern("tract", "g....")),
+            Err(SessionError::UnknownWord("tract".to_string()))
+        );
+        session.apply(pattern("light", "y....")).unwrap();
+        assert_eq!(session.candidates(), words(&["below", "elbow"]));
+        assert_eq!(session.split(&"below".parse().unwrap()).largest(), 1);
+
+        assert_eq!(session.ban(words(&["elbow", "crate"])), words(&["crate"]));
+        assert_eq!(session.candidates(), words(&["below"]));
+        assert_eq!(session.undo(), Some(&Step::Ban(words(&["elbow"]))));
+        assert_eq!(session.candidates(), words(&["below", "elbow"]));
+
+        session.set_mode(Mode::Hard);
+        let ranking = session
+            .rank(&NaiveGuesser, &RankBudget::unlimited(), &mut |_| {})
+            .unwrap();
+        assert!(ranking.complete);
+        assert_eq!(ranking.guesses.len(), 2);
+
+        session.apply(pattern("below", ".....")).unwrap();
+        assert_eq!(
+            session
+                .rank(&NaiveGuesser, &RankBudget::unlimited(), &mut |_| {})
+                .map(|ranking| ranking.complete),
+            Err(SessionError::NoCandidates)
+        );
+
+        session.reset();
+        assert_eq!(session.mode(), Mode::Normal);
+        assert_eq!(session.candidates(), dictionary.answers);
+        assert_eq!(session.redo(), None);
+    }
+}
@@ -0,0 +1,158 @@
+use itertools::Itertools;
+
+use crate::words::{description_colors, LetterType, Pattern, Word};
+
+const SQUARES: [char; 6] = ['🟩', '🟨', '⬛', '⬜', '🟧', '🟦'];
+
+/// A row of a shared result grid, e.g. `⬛🟨⬛⬛🟩`. `None` if the line isn't exactly `N`
+/// colored squares.
+pub fn parse_row<const N: usize>(line: &str) -> Option<[LetterType; N]> {
+    let line = line.trim();
+    if !line
+        .chars()
+        .all(|c| SQUARES.contains(&c) || c == '\u{fe0f}')
+    {
+        return None;
+    }
+
+    description_colors(line).collect::<Vec<_>>().try_into().ok()
+}
+
+/// Rows of a shared result grid, other lines like the `Wordle 1,234 3/6` header are skipped.
+pub fn parse_grid<const N: usize>(text: &str) -> Vec<[LetterType; N]> {
+    text.lines().filter_map(parse_row).collect()
+}
+
+pub fn row_to_emoji<const N: usize>(row: &[LetterType; N]) -> String {
+    row.iter()
+        .map(|letter_type| match letter_type {
+            LetterType::Green => '🟩',
+            LetterType::Yellow => '🟨',
+            LetterType::Gray => '⬛',
+        })
+        .collect()
+}
+
+/// For every row, the words from `valid` that get its colors when guessed against `answer`.
+pub fn analyse_grid<const N: usize>(
+    rows: &[[LetterType; N]],
+    answer: &Word<N>,
+    valid: &[Word<N>],
+) -> Vec<Vec<Word<N>>> {
+    let colors: Vec<_> = valid
+        .iter()
+        .map(|guess| Pattern::from_guess(guess, answer).colors())
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            std::iter::zip(valid, &colors)
+                .filter(|(_, colors)| *colors == row)
+                .map(|(guess, _)| *guess)
+                .collect()
+        })
+        .collect()
+}
+
+/// The standard share block: a title with the number of guesses, `X` if the game isn't solved,
+/// then a row of squares per guess.
+pub fn share_block<const N: usize>(title: &str, patterns: &[Pattern<N>]) -> String {
+    let score = match patterns.last() {
+        Some(pattern) if pattern.is_solved() => patterns.len().to_string(),
+        _ => "X".to_string(),
+    };
+
+    std::iter::once(format!("{title} {score}/6\n"))
+        .chain(
+            patterns
+                .iter()
+                .map(|pattern| row_to_emoji(&pattern.colors())),
+        )
+        .join("\n")
+}
+
+/// Spoiler-free line with the number of answers left after every guess.
+pub fn counts_line(answers_left: &[usize]) -> String {
+    answers_left.iter().join(" → ")
+}
+
+pub struct TranscriptStep<const N: usize> {
+    pub pattern: Pattern<N>,
+    pub answers_left: usize,
+    /// Best guesses for this step by the guesser, with their ranks.
+    pub alternatives: Vec<(Word<N>, f32)>,
+}
+
+/// Markdown transcript of a game: the share block followed by a table of the steps.
+pub fn transcript<const N: usize>(title: &str, steps: &[TranscriptStep<N>]) -> String {
+    let patterns: Vec<_> = steps.iter().map(|step| step.pattern).collect();
+    let mut markdown = format!("```\n{}\n```\n\n", share_block(title, &patterns));
+
+    markdown += "| # | guess | colors | answers left | best guesses |\n";
+    markdown += "|---|-------|--------|--------------|--------------|\n";
+    for (i, step) in steps.iter().enumerate() {
+        let alternatives = step
+            .alternatives
+            .iter()
+            .map(|(guess, rank)| format!("{guess} ({rank:.3})"))
+            .join(", ");
+        markdown += &format!(
+            "| {} | {} | {} | {} | {alternatives} |\n",
+            i + 1,
+            step.pattern.word(),
+            row_to_emoji(&step.pattern.colors()),
+            step.answers_left,
+        );
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::words;
+
+    #[test]
+    fn test_parse_grid() {
+        let text = "Wordle 1,234 3/6\n\n⬛🟨⬛⬛🟩\n⬜⬜\u{fe0f}🟩🟨⬜\n🟩🟩🟩🟩🟩\n";
+        let rows = parse_grid::<5>(text);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(row_to_emoji(&rows[0]), "⬛🟨⬛⬛🟩");
+        assert_eq!(row_to_emoji(&rows[1]), "⬛⬛🟩🟨⬛");
+        assert_eq!(row_to_emoji(&rows[2]), "🟩🟩🟩🟩🟩");
+
+        assert_eq!(parse_row::<5>("⬛🟨⬛⬛"), None);
+        assert_eq!(parse_row::<5>("crane"), None);
+    }
+
+    #[test]
+    fn test_share_block() {
+        let patterns = [
+            Pattern::<5>::from_description("slate", "..g.g").unwrap(),
+            Pattern::<5>::from_description("trace", "ggggg").unwrap(),
+        ];
+
+        assert_eq!(
+            share_block("rustybovich", &patterns),
+            "rustybovich 2/6\n\n⬛⬛🟩⬛🟩\n🟩🟩🟩🟩🟩"
+        );
+        assert_eq!(
+            share_block("rustybovich", &patterns[..1]),
+            "rustybovich X/6\n\n⬛⬛🟩⬛🟩"
+        );
+        assert_eq!(counts_line(&[12, 1]), "12 → 1");
+    }
+
+    #[test]
+    fn test_analyse_grid() {
+        let valid = words(&["crate", "slate", "trace", "light", "below"]);
+        let answer = "trace".parse().unwrap();
+        let rows = parse_grid::<5>("⬛⬛🟩🟨🟩\n🟨🟩🟩🟨🟩\n🟩🟩🟩🟩🟩\n🟨🟨🟨🟨🟨");
+
+        assert_eq!(
+            analyse_grid(&rows, &answer, &valid),
+            vec![vec![valid[1]], vec![valid[0]], vec![valid[2]], vec![],]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! JavaScript API for WebAssembly, built with e.g.
+//! `wasm-pack build --target web -- --no-default-features --features wasm`.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    guesser::{GuesserRegistry, RankBudget},
+    session::{Mode, SolverSession},
+    words::{Pattern, Word},
+    Dictionary, WORD_LENGTH as N,
+};
+
+#[derive(Serialize)]
+struct RankedGuess {
+    word: Word<N>,
+    rank: f32,
+}
+
+fn js_error(e: impl ToString) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// A game being solved: the dictionary and the patterns the guesses got so far.
+#[wasm_bindgen]
+pub struct Solver {
+    guessers: GuesserRegistry<N>,
+    session: SolverSession<'static, N>,
+}
+
+#[wasm_bindgen]
+impl Solver {
+    /// A solver for a dictionary in the JSON format of the dictionary files.
+    #[wasm_bindgen(constructor)]
+    pub fn new(dictionary: &str, lies: usize) -> Result<Solver, JsError> {
+        let dictionary = Dictionary::from_json(dictionary).map_err(js_error)?;
+        Ok(Self {
+            guessers: GuesserRegistry::default(),
+            session: SolverSession::shared(Arc::new(dictionary), lies),
+        })
+    }
+
+    /// Applies the colors a guess got, e.g. `("crate", "..y.g")`, returns the number of answers
+    /// left.
+    #[wasm_bindgen(js_name = applyPattern)]
+    pub fn apply_pattern(&mut self, word: &str, colors: &str) -> Result<usize, JsError> {
+        let pattern = Pattern::<N>::from_description(word, colors)
+            .map_err(|_| js_error(format!("`{word} {colors}` isn't a pattern of {N} letters")))?;
+        self.session.apply(pattern).map_err(js_error)?;
+        Ok(self.session.candidates().len())
+    }
+
+    /// The answers left.
+    pub fn candidates(&self) -> Vec<String> {
+        self.session
+            .candidates()
+            .iter()
+            .map(Word::to_string)
+            .collect()
+    }
+
+    /// Starts over with every answer of the dictionary.
+    pub fn reset(&mut self) {
+        self.session.reset();
+    }
+
+    pub fn guessers(&self) -> Vec<String> {
+        self.guessers.names().map(String::from).collect()
+    }
+
+    /// The `top` best guesses by the guesser named `guesser`, as `{word, rank}` objects, lower
+    /// ranks are better. Only the answers left are ranked in hard mode.
+    #[wasm_bindgen(js_name = rankGuesses)]
+    pub fn rank_guesses(
+        &mut self,
+        guesser: &str,
+        top: usize,
+        hard: bool,
+    ) -> Result<JsValue, JsError> {
+        let guesser = self.guessers.get(guesser).map_err(js_error)?.guesser();
+
+        // the guessers don't need the patterns prepared, and preparing them for every guess and
+        // answer would take too much memory for a web page
+        self.session.set_mode(match hard {
+            true => Mode::Hard,
+            false => Mode::Normal,
+        });
+        let ranking = self
+            .session
+            .rank(guesser, &RankBudget::unlimited(), &mut |_| {})
+            .map_err(js_error)?;
+
+        let ranked: Vec<_> = ranking
+            .guesses
+            .into_iter()
+            .take(top)
+            .map(|(word, rank)| RankedGuess { word, rank })
+            .collect();
+        serde_wasm_bindgen::to_value(&ranked).map_err(js_error)
+    }
+}
@@ -1,6 +1,7 @@
 use std::{collections::HashMap, mem::MaybeUninit, str::FromStr};
 
 use itertools::Itertools;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Word<const N: usize> {
@@ -13,34 +14,72 @@ impl<const N: usize> Word<N> {
     }
 
     pub fn matches(&self, pattern: &Pattern<N>) -> bool {
+        self.mismatch(pattern).is_none()
+    }
+
+    /// The first reason the word can't be the answer that got `pattern`, `None` if it matches.
+    pub fn mismatch(&self, pattern: &Pattern<N>) -> Option<Mismatch> {
         let mut letters: [_; N] = self.iter().cloned().map(Some).collect_array().unwrap();
 
-        let green_matches = std::iter::zip(letters.iter_mut(), pattern.iter())
-            .filter(|(_, pattern_letter)| pattern_letter.letter_type == LetterType::Green)
-            .all(|(letter_option, pattern_letter)| {
-                letter_option
-                    .take_if(|c| *c == pattern_letter.letter)
-                    .is_some()
-            });
+        let green_mismatch = std::iter::zip(letters.iter_mut(), pattern.iter())
+            .enumerate()
+            .filter(|(_, (_, pattern_letter))| pattern_letter.letter_type == LetterType::Green)
+            .map(|(position, (letter_option, pattern_letter))| {
+                let taken = letter_option.take_if(|c| *c == pattern_letter.letter);
+                (position, pattern_letter, taken.is_some())
+            })
+            .find(|&(_, _, taken)| !taken);
 
-        if !green_matches {
-            return false;
+        if let Some((position, pattern_letter, _)) = green_mismatch {
+            return Some(Mismatch::MissingGreen {
+                letter: pattern_letter.letter,
+                position,
+            });
         }
 
-        pattern
-            .iter()
-            .enumerate()
-            .all(|(i, &pattern_letter)| match pattern_letter.letter_type {
-                LetterType::Green => true,
-                LetterType::Yellow => letters
-                    .iter()
-                    .position(|&other| other.is_some_and(|c| c == pattern_letter.letter))
-                    .and_then(|j| letters[j].take_if(|_| i != j))
-                    .is_some(),
-                LetterType::Gray => letters
-                    .iter()
-                    .all(|&other| other.map_or(true, |c| c != pattern_letter.letter)),
-            })
+        let shown = |letter| {
+            pattern
+                .iter()
+                .filter(|p| p.letter == letter && p.letter_type != LetterType::Gray)
+                .count()
+        };
+        let found = |letter| self.iter().filter(|&&c| c == letter).count();
+
+        pattern.iter().enumerate().find_map(|(i, &pattern_letter)| {
+            let letter = pattern_letter.letter;
+            match pattern_letter.letter_type {
+                LetterType::Green => None,
+                LetterType::Yellow => {
+                    match letters
+                        .iter()
+                        .position(|&other| other.is_some_and(|c| c == letter))
+                    {
+                        Some(j) if i == j => Some(Mismatch::MisplacedYellow {
+                            letter,
+                            position: i,
+                        }),
+                        Some(j) => {
+                            letters[j] = None;
+                            None
+                        }
+                        None => Some(Mismatch::TooFew {
+                            letter,
+                            needed: shown(letter),
+                            found: found(letter),
+                        }),
+                    }
+                }
+                LetterType::Gray => letters.contains(&Some(letter)).then(|| Mismatch::TooMany {
+                    letter,
+                    allowed: shown(letter),
+                    found: found(letter),
+                }),
+            }
+        })
+    }
+
+    pub fn is_alphabetic(&self) -> bool {
+        self.iter().all(|c| c.is_alphabetic())
     }
 
     fn iter(&self) -> std::slice::Iter<'_, char> {
@@ -63,9 +102,82 @@ impl<const N: usize> std::fmt::Display for Word<N> {
     }
 }
 
+/// Serialized as a string, e.g. `"crate"`.
+impl<const N: usize> Serialize for Word<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Word<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let word = String::deserialize(deserializer)?;
+        word.parse()
+            .map_err(|_| de::Error::custom(format!("`{word}` isn't a word of {N} letters")))
+    }
+}
+
+/// Why a word doesn't match a pattern, positions counting from 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mismatch {
+    MissingGreen {
+        letter: char,
+        position: usize,
+    },
+    /// The letter is where the pattern shows it yellow, so it must be somewhere else.
+    MisplacedYellow {
+        letter: char,
+        position: usize,
+    },
+    TooFew {
+        letter: char,
+        needed: usize,
+        found: usize,
+    },
+    TooMany {
+        letter: char,
+        allowed: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Mismatch::MissingGreen { letter, position } => {
+                write!(f, "needs green '{letter}' at position {}", position + 1)
+            }
+            Mismatch::MisplacedYellow { letter, position } => write!(
+                f,
+                "has '{letter}' at position {}, but the pattern shows it yellow there",
+                position + 1
+            ),
+            Mismatch::TooFew {
+                letter,
+                needed,
+                found,
+            } => match found {
+                0 => write!(f, "needs '{letter}', but has none"),
+                _ => write!(f, "needs {needed} '{letter}'s, but has {found}"),
+            },
+            Mismatch::TooMany {
+                letter,
+                allowed,
+                found,
+            } => match allowed {
+                0 => write!(f, "has '{letter}', but the pattern shows none"),
+                _ => write!(
+                    f,
+                    "has {found} '{letter}'s, but the pattern shows only {allowed}"
+                ),
+            },
+        }
+    }
+}
+
 pub type PatternCache<'a, const N: usize> = HashMap<(&'a Word<N>, &'a Word<N>), Pattern<N>>;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Pattern<const N: usize> {
     pub pattern: [PatternLetter; N],
 }
@@ -75,12 +187,19 @@ impl<const N: usize> Pattern<N> {
         Self { pattern }
     }
 
+    /// `descr` has a color for every letter: `g`, `1` or 🟩 for green, `y`, `2` or 🟨 for yellow
+    /// and anything else for gray. 🟧 and 🟦 of the high contrast mode are green and yellow.
+    /// Both `word` and `descr` must be `N` long.
     pub fn from_description(word: &str, descr: &str) -> Result<Pattern<N>, IteratorIntoArrayError> {
-        let pattern = std::iter::zip(word.chars(), descr.chars())
+        if word.chars().count() != N || description_colors(descr).count() != N {
+            return Err(IteratorIntoArrayError);
+        }
+
+        let pattern = std::iter::zip(word.chars(), description_colors(descr))
             .map(|(letter, color)| match color {
-                'g' | '1' => PatternLetter::green(letter),
-                'y' | '2' => PatternLetter::yellow(letter),
-                _ => PatternLetter::gray(letter),
+                LetterType::Green => PatternLetter::green(letter),
+                LetterType::Yellow => PatternLetter::yellow(letter),
+                LetterType::Gray => PatternLetter::gray(letter),
             })
             .collect_array()?;
 
@@ -166,10 +285,96 @@ impl<const N: usize> Pattern<N> {
             .collect()
     }
 
+    /// Like [`Pattern::filter_words`], but for feedback where exactly `lies` letters show a
+    /// wrong color.
+    pub fn filter_words_with_lies(&self, words: &[Word<N>], lies: usize) -> Vec<Word<N>> {
+        words
+            .iter()
+            .filter(|word| self.is_consistent(word, lies))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `word` could be the answer if exactly `lies` letters of the pattern are colored
+    /// wrong.
+    pub fn is_consistent(&self, word: &Word<N>, lies: usize) -> bool {
+        if lies == 0 {
+            return self.match_word(word);
+        }
+        Pattern::from_guess(&self.word(), word).distance(self) == lies
+    }
+
+    /// Number of letters colored differently in the two patterns.
+    pub fn distance(&self, other: &Pattern<N>) -> usize {
+        std::iter::zip(self.iter(), other.iter())
+            .filter(|(a, b)| a.letter_type != b.letter_type)
+            .count()
+    }
+
+    /// Every pattern that could be reported instead of this one with exactly `lies` letters
+    /// colored wrong.
+    pub fn lie_variants(&self, lies: usize) -> Vec<Pattern<N>> {
+        use LetterType::*;
+
+        if lies == 0 {
+            return vec![*self];
+        }
+
+        (0..N)
+            .combinations(lies)
+            .flat_map(|positions| {
+                positions
+                    .iter()
+                    .map(|&i| {
+                        [Green, Yellow, Gray]
+                            .into_iter()
+                            .filter(move |&letter_type| letter_type != self.pattern[i].letter_type)
+                            .map(move |letter_type| (i, letter_type))
+                    })
+                    .multi_cartesian_product()
+                    .map(|changes| {
+                        let mut variant = *self;
+                        for (i, letter_type) in changes {
+                            variant.pattern[i].letter_type = letter_type;
+                        }
+                        variant
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, PatternLetter> {
         self.pattern.iter()
     }
 
+    pub fn colors(&self) -> [LetterType; N] {
+        self.pattern
+            .map(|pattern_letter| pattern_letter.letter_type)
+    }
+
+    /// Colors in the format of [`Pattern::from_description`], e.g. `gg..y`.
+    pub fn to_description(&self) -> String {
+        self.iter()
+            .map(|pattern_letter| match pattern_letter.letter_type {
+                LetterType::Green => 'g',
+                LetterType::Yellow => 'y',
+                LetterType::Gray => '.',
+            })
+            .collect()
+    }
+
+    /// Whether every letter is green.
+    pub fn is_solved(&self) -> bool {
+        self.iter()
+            .all(|pattern_letter| pattern_letter.letter_type == LetterType::Green)
+    }
+
+    /// The guess this pattern was given for.
+    pub fn word(&self) -> Word<N> {
+        Word::new(self.pattern.map(|pattern_letter| pattern_letter.letter))
+    }
+
     pub fn prepare_all<'a>(
         valid_guesses: &'a [Word<N>],
         possible_answers: &'a [Word<N>],
@@ -195,20 +400,86 @@ impl<const N: usize> std::fmt::Display for Pattern<N> {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+struct PatternSerialized {
+    word: String,
+    colors: String,
+}
+
+/// Serialized as the guess and its colors, e.g. `{"word": "crate", "colors": "..y.g"}`.
+impl<const N: usize> Serialize for Pattern<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PatternSerialized {
+            word: self.word().to_string(),
+            colors: self.to_description(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Pattern<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let PatternSerialized { word, colors } = PatternSerialized::deserialize(deserializer)?;
+        Pattern::from_description(&word, &colors).map_err(|_| {
+            de::Error::custom(format!("`{word} {colors}` isn't a pattern of {N} letters"))
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LetterType {
     Green,
     Yellow,
     Gray,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Colors of a description accepted by [`Pattern::from_description`]. Emoji variation
+/// selectors, which some platforms add after ⬛ and ⬜, are skipped.
+pub fn description_colors(descr: &str) -> impl Iterator<Item = LetterType> + '_ {
+    descr.chars().filter(|&c| c != '\u{fe0f}').map(|c| match c {
+        'g' | '1' | '🟩' | '🟧' => LetterType::Green,
+        'y' | '2' | '🟨' | '🟦' => LetterType::Yellow,
+        _ => LetterType::Gray,
+    })
+}
+
+/// Whether `c` is one of the usual color characters rather than a typo, which
+/// [`description_colors`] would take for gray.
+pub fn is_description_color(c: char) -> bool {
+    matches!(
+        c,
+        'g' | 'y'
+            | '.'
+            | '1'
+            | '2'
+            | '0'
+            | '-'
+            | '_'
+            | '🟩'
+            | '🟨'
+            | '⬛'
+            | '⬜'
+            | '🟧'
+            | '🟦'
+            | '\u{fe0f}'
+    )
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct PatternLetter {
     letter_type: LetterType,
     letter: char,
 }
 
 impl PatternLetter {
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    pub fn letter_type(&self) -> LetterType {
+        self.letter_type
+    }
+
     fn green(letter: char) -> Self {
         Self {
             letter_type: LetterType::Green,
@@ -231,6 +502,7 @@ impl PatternLetter {
     }
 }
 
+#[cfg(feature = "color")]
 impl std::fmt::Display for PatternLetter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use colored::*;
@@ -244,6 +516,14 @@ impl std::fmt::Display for PatternLetter {
     }
 }
 
+/// Without terminal colors only the letter, see [`Pattern::to_description`] for the colors.
+#[cfg(not(feature = "color"))]
+impl std::fmt::Display for PatternLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.letter)
+    }
+}
+
 trait WrapLetter {
     fn green(self) -> PatternLetter;
     fn yellow(self) -> PatternLetter;
@@ -297,6 +577,7 @@ impl<const N: usize, I: std::iter::Iterator> CollectArray<N> for I {}
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::test_util::words;
 
     #[test]
     fn test_parse_word() {
@@ -315,6 +596,67 @@ pub mod tests {
         assert_eq!(Word::<3>::from_str("foo"), Ok(Word::new(['f', 'o', 'o'])))
     }
 
+    #[test]
+    fn test_word_mismatch() {
+        let mismatch = |word: &str, guess: &str, colors: &str| {
+            let pattern = Pattern::<5>::from_description(guess, colors).unwrap();
+            Word::from_str(word).unwrap().mismatch(&pattern)
+        };
+
+        assert_eq!(mismatch("crate", "trace", "yggyg"), None);
+        assert_eq!(
+            mismatch("crate", "brine", "gg..g"),
+            Some(Mismatch::MissingGreen {
+                letter: 'b',
+                position: 0
+            })
+        );
+        assert_eq!(
+            mismatch("crate", "slate", "..ygg"),
+            Some(Mismatch::MisplacedYellow {
+                letter: 'a',
+                position: 2
+            })
+        );
+        assert_eq!(
+            mismatch("crate", "geese", "y...."),
+            Some(Mismatch::TooFew {
+                letter: 'g',
+                needed: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            mismatch("crepe", "eerie", "yy..g"),
+            Some(Mismatch::TooFew {
+                letter: 'e',
+                needed: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            mismatch("geese", "eerie", ".y..g"),
+            Some(Mismatch::TooMany {
+                letter: 'e',
+                allowed: 2,
+                found: 3
+            })
+        );
+        // a gray letter that isn't in the answer at all
+        assert_eq!(
+            mismatch("crate", "light", "....."),
+            Some(Mismatch::TooMany {
+                letter: 't',
+                allowed: 0,
+                found: 1
+            })
+        );
+        assert_eq!(
+            mismatch("geese", "eerie", ".y..g").unwrap().to_string(),
+            "has 3 'e's, but the pattern shows only 2"
+        );
+    }
+
     #[test]
     fn test_pattern_from_description() {
         assert_eq!(
@@ -366,6 +708,92 @@ pub mod tests {
             Pattern::from_description("bra", "gyy"),
             Ok(Pattern::new(['b'.green(), 'r'.yellow(), 'a'.yellow()]))
         );
+
+        assert_eq!(
+            Pattern::<5>::from_description("crate", "🟩🟩⬛⬜\u{fe0f}🟨"),
+            Pattern::from_description("crate", "gg..y")
+        );
+
+        assert_eq!(
+            Pattern::<5>::from_description("crate", "🟧🟦⬛⬛⬛"),
+            Pattern::from_description("crate", "gy...")
+        );
+
+        assert_eq!(
+            Pattern::<5>::from_description("crates", "....."),
+            Err(IteratorIntoArrayError)
+        );
+        assert_eq!(
+            Pattern::<5>::from_description("crate", "..gg.yyy"),
+            Err(IteratorIntoArrayError)
+        );
+        assert_eq!(
+            Pattern::<5>::from_description("crat", "....."),
+            Err(IteratorIntoArrayError)
+        );
+    }
+
+    #[test]
+    fn test_pattern_to_description() {
+        let pattern = Pattern::<5>::from_description("crate", "gg..y").unwrap();
+        assert_eq!(pattern.to_description(), "gg..y");
+        assert_eq!(pattern.word(), Word::from_str("crate").unwrap());
+
+        let pattern = Pattern::<3>::from_description("bra", "12x").unwrap();
+        assert_eq!(pattern.to_description(), "gy.");
+        assert_eq!(pattern.word(), Word::from_str("bra").unwrap());
+    }
+
+    #[test]
+    fn test_serde() {
+        let word = Word::<5>::from_str("crate").unwrap();
+        assert_eq!(serde_json::to_string(&word).unwrap(), r#""crate""#);
+        assert_eq!(serde_json::from_str::<Word<5>>(r#""crate""#).unwrap(), word);
+        assert!(serde_json::from_str::<Word<5>>(r#""crates""#).is_err());
+
+        let pattern = Pattern::<5>::from_description("crate", "gg..y").unwrap();
+        let json = r#"{"word":"crate","colors":"gg..y"}"#;
+        assert_eq!(serde_json::to_string(&pattern).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Pattern<5>>(json).unwrap(), pattern);
+        assert!(serde_json::from_str::<Pattern<5>>(r#"{"word":"crate","colors":"g"}"#).is_err());
+        assert!(
+            serde_json::from_str::<Pattern<5>>(r#"{"word":"crates","colors":"....."}"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<Pattern<5>>(r#"{"word":"crate","colors":"..gg.yyy"}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_lie_variants() {
+        let pattern = Pattern::<3>::from_description("bar", "g.y").unwrap();
+
+        assert_eq!(pattern.lie_variants(0), vec![pattern]);
+
+        let variants = pattern.lie_variants(1);
+        assert_eq!(variants.len(), 6);
+        assert!(variants.iter().all(|v| v.distance(&pattern) == 1));
+
+        let variants = pattern.lie_variants(2);
+        assert_eq!(variants.len(), 12);
+        assert!(variants.iter().all(|v| v.distance(&pattern) == 2));
+    }
+
+    #[test]
+    fn test_filter_words_with_lies() {
+        let valid = words(&["crate", "slate", "trace", "light"]);
+
+        // true pattern of `slate` against `crate` is "..ggg"
+        let pattern = Pattern::from_description("slate", "..ggg").unwrap();
+        assert_eq!(
+            pattern.filter_words_with_lies(&valid, 0),
+            pattern.filter_words(&valid)
+        );
+
+        let lying = Pattern::from_description("slate", "y.ggg").unwrap();
+        assert!(lying.filter_words_with_lies(&valid, 1).contains(&valid[0]));
+        assert!(!lying.filter_words(&valid).contains(&valid[0]));
+        assert!(!lying.filter_words_with_lies(&valid, 1).contains(&valid[1]));
     }
 
     pub fn word_and_pattern<const N: usize>(